@@ -54,7 +54,7 @@ async fn main() -> anyhow::Result<()> {
 
         let mut reply = reason.reply(&messages, &[], &[]).pin();
 
-        println!("");
+        println!();
 
         while let Some(event) = reply.sip().await {
             if let Some(text) = event.text() {
@@ -67,6 +67,6 @@ async fn main() -> anyhow::Result<()> {
         let reply = reply.await?;
         messages.extend(reply.outputs.into_iter().map(Message::Assistant));
 
-        println!("");
+        println!();
     }
 }
@@ -1,10 +1,10 @@
+use reason::mcp::Toolbox;
 use reason::tool;
-use reason::{Message, Output, Reason, Tool};
+use reason::{Message, Reason, RunEvent, Tool};
 
 use anyhow::bail;
 use sipper::Sipper;
 use techne::client::{self, Client};
-use techne::mcp;
 use techne::server::{self, Server};
 
 use std::env;
@@ -20,7 +20,7 @@ pub async fn main() -> anyhow::Result<()> {
         return Ok(run_mcp_server().await?);
     }
 
-    let mut mcp = {
+    let client = {
         print!("> URL of MCP server (blank to simulate one): ");
         io::stdout().flush()?;
 
@@ -39,21 +39,21 @@ pub async fn main() -> anyhow::Result<()> {
         }
     };
 
-    println!("");
+    println!();
 
-    let server = mcp.server().information();
+    let mut toolbox = Toolbox::new();
+    toolbox.connect(client).await?;
 
-    println!(
-        "- Connected to MCP server: {} ({})",
-        server.name, server.version,
-    );
+    for client in toolbox.clients() {
+        let server = client.server().information();
 
-    let tools: Vec<_> = mcp
-        .list_tools()
-        .await?
-        .into_iter()
-        .map(Tool::from)
-        .collect();
+        println!(
+            "- Connected to MCP server: {} ({})",
+            server.name, server.version,
+        );
+    }
+
+    let tools = toolbox.tools();
 
     println!("- Available tools:");
 
@@ -61,7 +61,7 @@ pub async fn main() -> anyhow::Result<()> {
         println!("    {}\n        {}", function.name, function.description);
     }
 
-    println!("");
+    println!();
     println!("- Booting {model}...");
 
     let mut boot = Reason::boot(model, reason::Backend::Cuda).pin();
@@ -77,96 +77,61 @@ pub async fn main() -> anyhow::Result<()> {
 
     let reason = boot.await?;
 
-    println!("");
+    println!();
     println!("-------------------");
     println!("Assistant is ready. Break the ice!");
     println!("-------------------");
 
     let mut messages = vec![Message::system("You are a helpful assistant.")];
     let mut message = String::new();
-    let mut is_processing = false;
 
     loop {
-        if !is_processing {
-            print!("\n> ");
-            io::stdout().flush()?;
-
-            let _ = io::stdin().read_line(&mut message)?;
+        print!("\n> ");
+        io::stdout().flush()?;
 
-            if message.trim().is_empty() {
-                if message.contains("\n") {
-                    message.clear();
-                    continue;
-                }
+        let _ = io::stdin().read_line(&mut message)?;
 
-                return Ok(());
+        if message.trim().is_empty() {
+            if message.contains("\n") {
+                message.clear();
+                continue;
             }
 
-            messages.push(Message::User(message.trim().to_owned()));
-            message.clear();
+            return Ok(());
         }
 
-        let mut reply = reason.reply(&messages, &[], &tools).pin();
+        messages.push(Message::User(message.trim().to_owned()));
+        message.clear();
+
+        let mut run = reason.run(&messages, &tools, &mut toolbox, 10).pin();
 
-        println!("");
+        println!();
 
-        while let Some(event) = reply.sip().await {
-            if let Some(text) = event.text() {
-                print!("{text}");
+        while let Some(event) = run.sip().await {
+            match event {
+                RunEvent::Replied(event) => {
+                    if let Some(text) = event.text() {
+                        print!("{text}");
+                    }
+                }
+                RunEvent::ToolCalled(tool::Call::Function {
+                    name, arguments, ..
+                }) => {
+                    println!();
+                    println!("=> {name}: {arguments}");
+                }
+                RunEvent::ToolResponded(response) => {
+                    println!("<= {content}", content = response.content);
+                    println!();
+                }
             }
 
             io::stdout().flush()?;
         }
 
-        println!("");
-
-        let reply = reply.await?;
-        is_processing = false;
+        println!();
 
-        for output in reply.outputs {
-            messages.push(Message::Assistant(output.clone()));
-
-            let Output::ToolCalls(tools) = output else {
-                continue;
-            };
-
-            for tool in tools {
-                let tool::Call::Function {
-                    id,
-                    name,
-                    arguments,
-                } = tool;
-
-                let Ok(arguments) = serde_json::from_str(&arguments) else {
-                    continue;
-                };
-
-                println!("=> {name}: {arguments}");
-
-                let response = mcp.call_tool(name, arguments).await?;
-
-                let content = match response.content {
-                    mcp::server::Content::Unstructured(items) => items
-                        .into_iter()
-                        .filter_map(|item| {
-                            if let mcp::server::content::Unstructured::Text { text } = item {
-                                Some(text)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
-                    mcp::server::Content::Structured(value) => serde_json::to_string(&value)?,
-                };
-
-                println!("<= {content}");
-                println!("");
-
-                messages.push(Message::Tool(tool::Response { id, content }));
-
-                is_processing = true;
-            }
-        }
+        messages.extend(run.await?);
     }
 }
 
@@ -0,0 +1,546 @@
+use crate::{Backend, Retry};
+
+use tokio::process;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootConfig {
+    pub backend: Backend,
+    pub executor: Executor,
+    pub port: Option<u16>,
+    pub context_size: Option<u32>,
+    pub threads: Option<u32>,
+    pub batch_size: Option<u32>,
+    pub parallel: Option<u32>,
+    pub gpu_layers: Option<u32>,
+    pub flash_attention: bool,
+    pub metrics: bool,
+    pub cache_type_k: Option<CacheType>,
+    pub cache_type_v: Option<CacheType>,
+    pub chat_template: Option<String>,
+    pub embedding: Option<Pooling>,
+    pub cache_dir: Option<PathBuf>,
+    pub timeout: Option<Duration>,
+    pub retry: Retry,
+    pub grace_period: Duration,
+    pub extra_args: Vec<String>,
+}
+
+impl BootConfig {
+    pub(crate) const GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+    pub fn new(backend: Backend) -> Self {
+        Self {
+            backend,
+            executor: Executor::Auto,
+            port: None,
+            context_size: None,
+            threads: None,
+            batch_size: None,
+            parallel: None,
+            gpu_layers: None,
+            flash_attention: false,
+            metrics: false,
+            cache_type_k: None,
+            cache_type_v: None,
+            chat_template: None,
+            embedding: None,
+            cache_dir: None,
+            timeout: None,
+            retry: Retry::default(),
+            grace_period: Self::GRACE_PERIOD,
+            extra_args: Vec::new(),
+        }
+    }
+
+    pub fn executor(self, executor: Executor) -> Self {
+        Self { executor, ..self }
+    }
+
+    pub fn port(self, port: u16) -> Self {
+        Self {
+            port: Some(port),
+            ..self
+        }
+    }
+
+    pub fn context_size(self, context_size: u32) -> Self {
+        Self {
+            context_size: Some(context_size),
+            ..self
+        }
+    }
+
+    pub fn threads(self, threads: u32) -> Self {
+        Self {
+            threads: Some(threads),
+            ..self
+        }
+    }
+
+    pub fn batch_size(self, batch_size: u32) -> Self {
+        Self {
+            batch_size: Some(batch_size),
+            ..self
+        }
+    }
+
+    pub fn parallel(self, parallel: u32) -> Self {
+        Self {
+            parallel: Some(parallel),
+            ..self
+        }
+    }
+
+    pub fn gpu_layers(self, gpu_layers: u32) -> Self {
+        Self {
+            gpu_layers: Some(gpu_layers),
+            ..self
+        }
+    }
+
+    pub fn flash_attention(self, flash_attention: bool) -> Self {
+        Self {
+            flash_attention,
+            ..self
+        }
+    }
+
+    pub fn metrics(self, metrics: bool) -> Self {
+        Self { metrics, ..self }
+    }
+
+    pub fn cache_type(self, cache_type: CacheType) -> Self {
+        Self {
+            cache_type_k: Some(cache_type),
+            cache_type_v: Some(cache_type),
+            ..self
+        }
+    }
+
+    pub fn cache_type_k(self, cache_type: CacheType) -> Self {
+        Self {
+            cache_type_k: Some(cache_type),
+            ..self
+        }
+    }
+
+    pub fn cache_type_v(self, cache_type: CacheType) -> Self {
+        Self {
+            cache_type_v: Some(cache_type),
+            ..self
+        }
+    }
+
+    pub fn chat_template(self, chat_template: impl Into<String>) -> Self {
+        Self {
+            chat_template: Some(chat_template.into()),
+            ..self
+        }
+    }
+
+    pub fn embedding(self, pooling: Pooling) -> Self {
+        Self {
+            embedding: Some(pooling),
+            ..self
+        }
+    }
+
+    pub fn cache_dir(self, cache_dir: impl AsRef<Path>) -> Self {
+        Self {
+            cache_dir: Some(cache_dir.as_ref().to_owned()),
+            ..self
+        }
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    pub fn retry(self, retry: Retry) -> Self {
+        Self { retry, ..self }
+    }
+
+    pub fn grace_period(self, grace_period: Duration) -> Self {
+        Self {
+            grace_period,
+            ..self
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.extra_args.push(arg.into());
+        self
+    }
+
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.extra_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub(crate) fn server_args(&self, default_gpu_layers: u32) -> Vec<String> {
+        let mut args = Vec::new();
+
+        let mut push = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(flag.to_owned());
+                args.push(value);
+            }
+        };
+
+        push("--ctx-size", self.context_size.map(|n| n.to_string()));
+        push("--threads", self.threads.map(|n| n.to_string()));
+        push("--batch-size", self.batch_size.map(|n| n.to_string()));
+        push("--parallel", self.parallel.map(|n| n.to_string()));
+
+        push(
+            "--gpu-layers",
+            self.gpu_layers
+                .or(self.backend.uses_gpu().then_some(default_gpu_layers))
+                .map(|n| n.to_string()),
+        );
+
+        push("--cache-type-k", self.cache_type_k.map(|t| t.to_string()));
+        push("--cache-type-v", self.cache_type_v.map(|t| t.to_string()));
+        push("--chat-template", self.chat_template.clone());
+
+        if self.flash_attention {
+            args.push("--flash-attn".to_owned());
+        }
+
+        if self.metrics {
+            args.push("--metrics".to_owned());
+        }
+
+        if let Some(pooling) = self.embedding {
+            args.push("--embedding".to_owned());
+            args.push("--pooling".to_owned());
+            args.push(pooling.to_string());
+        }
+
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
+
+impl From<Backend> for BootConfig {
+    fn from(backend: Backend) -> Self {
+        Self::new(backend)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Executor {
+    #[default]
+    Auto,
+    LlamaServer,
+    Container(Runtime),
+    Ollama,
+}
+
+impl Executor {
+    pub(crate) async fn detect(self) -> Option<(Self, Vec<u8>)> {
+        let candidates = match self {
+            Executor::Auto => [Executor::LlamaServer]
+                .into_iter()
+                .chain(Runtime::ALL.map(Executor::Container))
+                .chain([Executor::Ollama])
+                .collect(),
+            executor => vec![executor],
+        };
+
+        for executor in candidates {
+            match executor {
+                Executor::Auto => {}
+                Executor::LlamaServer => {
+                    if let Ok(version) = process::Command::new("llama-server")
+                        .arg("--version")
+                        .output()
+                        .await
+                    {
+                        return Some((executor, version.stdout));
+                    }
+                }
+                Executor::Ollama => {
+                    if let Ok(version) = process::Command::new("ollama")
+                        .arg("--version")
+                        .output()
+                        .await
+                    {
+                        return Some((executor, version.stdout));
+                    }
+                }
+                Executor::Container(runtime) => {
+                    if let Ok(version) = process::Command::new(runtime.command())
+                        .arg("version")
+                        .output()
+                        .await
+                        && version.status.success()
+                    {
+                        return Some((executor, version.stdout));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    Docker,
+    Podman,
+    Nerdctl,
+}
+
+impl Runtime {
+    pub const ALL: [Self; 3] = [Self::Docker, Self::Podman, Self::Nerdctl];
+
+    pub fn command(self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Podman => "podman",
+            Runtime::Nerdctl => "nerdctl",
+        }
+    }
+
+    pub(crate) fn gpu_flags(self) -> &'static str {
+        match self {
+            Runtime::Docker | Runtime::Nerdctl => "--gpus all",
+            Runtime::Podman => "--device nvidia.com/gpu=all",
+        }
+    }
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Runtime::Docker => "Docker",
+            Runtime::Podman => "Podman",
+            Runtime::Nerdctl => "nerdctl",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheType {
+    F32,
+    F16,
+    Bf16,
+    Q8_0,
+    Q5_0,
+    Q5_1,
+    Q4_0,
+    Q4_1,
+    Iq4Nl,
+}
+
+impl fmt::Display for CacheType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheType::F32 => "f32",
+            CacheType::F16 => "f16",
+            CacheType::Bf16 => "bf16",
+            CacheType::Q8_0 => "q8_0",
+            CacheType::Q5_0 => "q5_0",
+            CacheType::Q5_1 => "q5_1",
+            CacheType::Q4_0 => "q4_0",
+            CacheType::Q4_1 => "q4_1",
+            CacheType::Iq4Nl => "iq4_nl",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pooling {
+    None,
+    Mean,
+    Cls,
+    Last,
+    Rank,
+}
+
+impl fmt::Display for Pooling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Pooling::None => "none",
+            Pooling::Mean => "mean",
+            Pooling::Cls => "cls",
+            Pooling::Last => "last",
+            Pooling::Rank => "rank",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Downloading,
+    Detecting,
+    Pulling,
+    Preparing,
+    Launching,
+    LoadingModel,
+    LoadingTensors,
+    CreatingContext,
+    WarmingUp,
+    Ready,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Stage::Downloading => "Downloading model...",
+            Stage::Detecting => "Detecting executor...",
+            Stage::Pulling => "Pulling image...",
+            Stage::Preparing => "Preparing container...",
+            Stage::Launching => "Launching assistant...",
+            Stage::LoadingModel => "Loading model...",
+            Stage::LoadingTensors => "Loading tensors...",
+            Stage::CreatingContext => "Creating context...",
+            Stage::WarmingUp => "Warming up...",
+            Stage::Ready => "Ready!",
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Tracker {
+    layers: BTreeSet<String>,
+    pulled: BTreeSet<String>,
+    last: Option<(Stage, u32)>,
+}
+
+impl Tracker {
+    pub fn track(&mut self, line: &str) -> Option<(Stage, u32)> {
+        let progress = self.pull(line).or_else(|| Self::load(line))?;
+
+        if self.last.is_some_and(|last| last >= progress) {
+            return None;
+        }
+
+        self.last = Some(progress);
+
+        Some(progress)
+    }
+
+    fn pull(&mut self, line: &str) -> Option<(Stage, u32)> {
+        let (layer, status) = line.split_once(": ")?;
+
+        if layer.len() < 12 || !layer.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        match status.trim() {
+            "Pull complete" | "Already exists" => {
+                let _ = self.layers.insert(layer.to_owned());
+                let _ = self.pulled.insert(layer.to_owned());
+            }
+            _ => {
+                let _ = self.layers.insert(layer.to_owned());
+            }
+        }
+
+        let percent = self.pulled.len() * 100 / self.layers.len().max(1);
+
+        Some((Stage::Pulling, scale(percent as u32, 45..=60)))
+    }
+
+    fn load(line: &str) -> Option<(Stage, u32)> {
+        const PATTERNS: &[(&str, Stage, u32)] = &[
+            (
+                "llama_model_loader: loaded meta data",
+                Stage::LoadingModel,
+                65,
+            ),
+            ("print_info: file type", Stage::LoadingModel, 70),
+            ("llm_load_print_meta: file type", Stage::LoadingModel, 70),
+            (
+                "load_tensors: loading model tensors",
+                Stage::LoadingTensors,
+                75,
+            ),
+            ("llm_load_tensors: ggml ctx size", Stage::LoadingTensors, 75),
+            ("offloaded", Stage::LoadingTensors, 80),
+            ("CPU_Mapped model buffer size", Stage::LoadingTensors, 85),
+            ("llama_init_from_model", Stage::CreatingContext, 88),
+            ("llama_new_context_with_model", Stage::CreatingContext, 88),
+            ("llama_context:", Stage::CreatingContext, 88),
+            ("llama_kv_cache", Stage::CreatingContext, 90),
+            ("warming up the model", Stage::WarmingUp, 94),
+            ("model loaded", Stage::WarmingUp, 97),
+            ("server is listening", Stage::WarmingUp, 99),
+        ];
+
+        PATTERNS
+            .iter()
+            .find(|(pattern, _, _)| line.contains(pattern))
+            .map(|(_, stage, percent)| (*stage, *percent))
+    }
+}
+
+// Every stage reports progress of the whole boot, so stages with their own
+// progress are scaled into a fixed slice of it
+pub(crate) fn scale(percent: u32, range: RangeInclusive<u32>) -> u32 {
+    range.start() + (range.end() - range.start()) * percent.min(100) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reports_progress_of_the_whole_boot() {
+        let mut pulling = Tracker::default();
+
+        let pulled: Vec<_> = [
+            "0123456789ab: Pulling fs layer",
+            "ba9876543210: Pulling fs layer",
+            "0123456789ab: Pull complete",
+            "ba9876543210: Pull complete",
+        ]
+        .into_iter()
+        .filter_map(|line| pulling.track(line))
+        .collect();
+
+        let mut loading = Tracker::default();
+
+        let loaded: Vec<_> = [
+            "llama_model_loader: loaded meta data with 30 key-value pairs",
+            "load_tensors: loading model tensors, this can take a while...",
+            "llama_kv_cache: size = 256.00 MiB",
+            "main: server is listening on http://0.0.0.0:8080",
+        ]
+        .into_iter()
+        .filter_map(|line| loading.track(line))
+        .collect();
+
+        assert_eq!(
+            pulled,
+            [
+                (Stage::Pulling, 45),
+                (Stage::Pulling, 52),
+                (Stage::Pulling, 60)
+            ]
+        );
+        assert_eq!(
+            loaded,
+            [
+                (Stage::LoadingModel, 65),
+                (Stage::LoadingTensors, 75),
+                (Stage::CreatingContext, 90),
+                (Stage::WarmingUp, 99),
+            ]
+        );
+        assert_eq!(scale(0, 0..=40), 0);
+        assert_eq!(scale(100, 40..=45), 45);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::Error;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    recordings: Arc<Mutex<Option<Recordings>>>,
+}
+
+type Recordings = HashMap<String, VecDeque<Vec<Vec<u8>>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    hash: String,
+    request: serde_json::Value,
+    chunks: Vec<String>,
+}
+
+impl Cassette {
+    pub(crate) const HOST: &'static str = "http://cassette.invalid";
+
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self::new(path, Mode::Record)
+    }
+
+    pub fn replay(path: impl AsRef<Path>) -> Self {
+        Self::new(path, Mode::Replay)
+    }
+
+    fn new(path: impl AsRef<Path>, mode: Mode) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            mode,
+            recordings: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.mode == Mode::Record
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    pub(crate) fn hash(body: &[u8]) -> String {
+        Sha256::digest(body)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    pub(crate) async fn play(&self, body: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        let hash = Self::hash(body);
+        let mut recordings = self.recordings.lock().await;
+
+        if recordings.is_none() {
+            let mut loaded = Recordings::new();
+
+            for line in fs::read_to_string(&self.path).await?.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let entry: Entry = serde_json::from_str(line)?;
+
+                let chunks = entry
+                    .chunks
+                    .iter()
+                    .map(|chunk| base64::engine::general_purpose::STANDARD.decode(chunk))
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| Error::CassetteCorrupted(self.path.clone()))?;
+
+                loaded.entry(entry.hash).or_default().push_back(chunks);
+            }
+
+            *recordings = Some(loaded);
+        }
+
+        let takes = recordings
+            .as_mut()
+            .and_then(|recordings| recordings.get_mut(&hash))
+            .ok_or_else(|| Error::CassetteMissed(hash.clone()))?;
+
+        // Identical requests are served in recording order; the last take is repeated
+        if takes.len() > 1 {
+            Ok(takes.pop_front().unwrap_or_default())
+        } else {
+            Ok(takes.front().cloned().unwrap_or_default())
+        }
+    }
+
+    pub(crate) async fn store(&self, body: &[u8], chunks: &[Vec<u8>]) -> Result<(), Error> {
+        let entry = Entry {
+            hash: Self::hash(body),
+            request: serde_json::from_slice(body).unwrap_or(serde_json::Value::Null),
+            chunks: chunks
+                .iter()
+                .map(|chunk| base64::engine::general_purpose::STANDARD.encode(chunk))
+                .collect(),
+        };
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        let _recordings = self.recordings.lock().await;
+
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+pub(crate) enum Chunks {
+    Live(reqwest::Response),
+    Replay(std::vec::IntoIter<Vec<u8>>),
+}
+
+impl Chunks {
+    pub async fn next(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        match self {
+            Chunks::Live(response) => Ok(response.chunk().await?.map(Vec::from)),
+            Chunks::Replay(chunks) => Ok(chunks.next()),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{Error, Provider, Retry};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use url::Url;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct ConnectConfig {
+    pub retry: Retry,
+    pub api_key: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub proxy: Option<Url>,
+    pub root_certificates: Vec<Vec<u8>>,
+    pub accept_invalid_certificates: bool,
+    pub provider: Option<Arc<dyn Provider>>,
+}
+
+impl ConnectConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retry(self, retry: Retry) -> Self {
+        Self { retry, ..self }
+    }
+
+    pub fn api_key(self, api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: Some(api_key.into()),
+            ..self
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    pub fn connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            connect_timeout: Some(connect_timeout),
+            ..self
+        }
+    }
+
+    pub fn proxy(self, proxy: Url) -> Self {
+        Self {
+            proxy: Some(proxy),
+            ..self
+        }
+    }
+
+    pub fn root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn accept_invalid_certificates(self, accept_invalid_certificates: bool) -> Self {
+        Self {
+            accept_invalid_certificates,
+            ..self
+        }
+    }
+
+    pub fn provider(self, provider: impl Provider + 'static) -> Self {
+        Self {
+            provider: Some(Arc::new(provider)),
+            ..self
+        }
+    }
+
+    pub(crate) fn client(&self, provider: &dyn Provider) -> Result<reqwest::Client, Error> {
+        let mut headers = HeaderMap::new();
+
+        if let Some(api_key) = &self.api_key {
+            let (name, value) = provider.authorization(api_key);
+
+            let mut value = HeaderValue::from_str(&value)
+                .map_err(|_| Error::InvalidHeader(name.to_string()))?;
+
+            value.set_sensitive(true);
+
+            let _ = headers.insert(name, value);
+        }
+
+        for (name, value) in &self.headers {
+            let invalid = || Error::InvalidHeader(name.clone());
+
+            let _ = headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+                HeaderValue::from_str(value).map_err(|_| invalid())?,
+            );
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .default_headers(headers)
+            .danger_accept_invalid_certs(self.accept_invalid_certificates);
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.clone())?);
+        }
+
+        for pem in &self.root_certificates {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(pem)?);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+impl Default for ConnectConfig {
+    fn default() -> Self {
+        Self {
+            retry: Retry::forever(),
+            api_key: None,
+            headers: Vec::new(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            root_certificates: Vec::new(),
+            accept_invalid_certificates: false,
+            provider: None,
+        }
+    }
+}
+
+impl PartialEq for ConnectConfig {
+    fn eq(&self, other: &Self) -> bool {
+        let is_same_provider = match (&self.provider, &other.provider) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.retry == other.retry
+            && self.api_key == other.api_key
+            && self.headers == other.headers
+            && self.timeout == other.timeout
+            && self.connect_timeout == other.connect_timeout
+            && self.proxy == other.proxy
+            && self.root_certificates == other.root_certificates
+            && self.accept_invalid_certificates == other.accept_invalid_certificates
+            && is_same_provider
+    }
+}
@@ -1,23 +1,66 @@
+use crate::Message;
+
 use tokio::task;
 
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
     #[error("request failed: {0}")]
     RequestFailed(Arc<reqwest::Error>),
+    #[error("completion failed: {0}")]
+    CompletionFailed(String),
     #[error("io operation failed: {0}")]
     IOFailed(Arc<io::Error>),
-    #[error("docker operation failed: {0}")]
-    DockerFailed(&'static str),
-    #[error("executor failed: {0}")]
-    ExecutorFailed(&'static str),
+    #[error("container operation failed: {0}")]
+    ContainerFailed(&'static str),
+    #[error("executor failed: {reason}{}", format_logs(logs))]
+    ExecutorFailed {
+        reason: &'static str,
+        logs: Vec<String>,
+    },
+    #[error("{provider} does not support {feature}")]
+    Unsupported {
+        provider: &'static str,
+        feature: &'static str,
+    },
+    #[error("only booted servers can be restarted")]
+    RestartUnsupported,
+    #[error("boot timed out after {0:?}")]
+    BootTimedOut(Duration),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("invalid url: {0}")]
+    InvalidUrl(url::ParseError),
+    #[error("checksum mismatch (expected: {expected}, found: {found})")]
+    ChecksumMismatched { expected: String, found: String },
+    #[error("no recorded completion matches the request (hash: {0})")]
+    CassetteMissed(String),
+    #[error("cassette is corrupted: {}", .0.display())]
+    CassetteCorrupted(PathBuf),
+    #[error("image data is not valid base64")]
+    InvalidImage,
+    #[error("unsupported format version: {0}")]
+    UnsupportedVersion(u32),
     #[error("deserialization failed: {0}")]
     SerdeFailed(Arc<serde_json::Error>),
+    #[error("output does not match the schema: {0}")]
+    SchemaMismatched(Arc<serde_json::Error>),
     #[error("task join failed: {0}")]
     JoinFailed(Arc<task::JoinError>),
-    #[error("no suitable executor was found: neither llama-server nor docker are installed")]
+    #[error("tool not found: {0}")]
+    ToolNotFound(String),
+    #[error("tool loop did not finish after {iterations} iterations")]
+    IterationLimitReached {
+        iterations: usize,
+        messages: Vec<Message>,
+    },
+    #[error(
+        "no suitable executor was found: neither llama-server, a container runtime nor ollama are installed"
+    )]
     NoExecutorAvailable,
 }
 
@@ -44,3 +87,13 @@ impl From<task::JoinError> for Error {
         Self::JoinFailed(Arc::new(error))
     }
 }
+
+impl From<url::ParseError> for Error {
+    fn from(error: url::ParseError) -> Self {
+        Self::InvalidUrl(error)
+    }
+}
+
+fn format_logs(logs: &[String]) -> String {
+    logs.iter().map(|log| format!("\n    {log}")).collect()
+}
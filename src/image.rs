@@ -0,0 +1,110 @@
+use crate::Error;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use url::Url;
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "Raw", try_from = "Raw")]
+pub enum Image {
+    Data { mime: String, bytes: Arc<[u8]> },
+    Url(Url),
+}
+
+impl Image {
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).await?;
+
+        let mime = match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("png") => "image/png",
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("bmp") => "image/bmp",
+            _ => "application/octet-stream",
+        };
+
+        Ok(Self::from_bytes(bytes, mime))
+    }
+
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, mime: impl Into<String>) -> Self {
+        Self::Data {
+            mime: mime.into(),
+            bytes: bytes.into(),
+        }
+    }
+
+    pub fn from_url(url: Url) -> Self {
+        Self::Url(url)
+    }
+
+    pub fn to_url(&self) -> String {
+        match self {
+            Self::Data { mime, bytes } => format!(
+                "data:{mime};base64,{data}",
+                data = base64::engine::general_purpose::STANDARD.encode(bytes)
+            ),
+            Self::Url(url) => url.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Raw {
+    Data { mime: String, data: String },
+    Url { url: String },
+}
+
+impl From<Image> for Raw {
+    fn from(image: Image) -> Self {
+        match image {
+            Image::Data { mime, bytes } => Raw::Data {
+                mime,
+                data: base64::engine::general_purpose::STANDARD.encode(bytes),
+            },
+            Image::Url(url) => Raw::Url { url: url.into() },
+        }
+    }
+}
+
+impl TryFrom<Raw> for Image {
+    type Error = Error;
+
+    fn try_from(raw: Raw) -> Result<Self, Self::Error> {
+        Ok(match raw {
+            Raw::Data { mime, data } => Image::Data {
+                mime,
+                bytes: base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .map_err(|_| Error::InvalidImage)?
+                    .into(),
+            },
+            Raw::Url { url } => Image::Url(Url::parse(&url)?),
+        })
+    }
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Data { mime, bytes } => f
+                .debug_struct("Data")
+                .field("mime", mime)
+                .field("bytes", &bytes.len())
+                .finish(),
+            Self::Url(url) => f.debug_tuple("Url").field(&url.as_str()).finish(),
+        }
+    }
+}
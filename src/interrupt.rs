@@ -0,0 +1,59 @@
+use tokio::sync::Notify;
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool};
+
+#[derive(Clone, Default)]
+pub struct Interrupt(Arc<State>);
+
+#[derive(Default)]
+struct State {
+    is_interrupted: AtomicBool,
+    notify: Notify,
+}
+
+impl Interrupt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn interrupt(&self) {
+        self.0.is_interrupted.store(true, atomic::Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn reset(&self) {
+        self.0.is_interrupted.store(false, atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.0.is_interrupted.load(atomic::Ordering::SeqCst)
+    }
+
+    pub async fn interrupted(&self) {
+        loop {
+            let notified = self.0.notify.notified();
+
+            if self.is_interrupted() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+impl PartialEq for Interrupt {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Interrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Interrupt")
+            .field(&self.is_interrupted())
+            .finish()
+    }
+}
@@ -1,21 +1,55 @@
+mod boot;
+mod cassette;
+mod connect;
 mod error;
-
+mod image;
+mod interrupt;
+mod monitor;
+mod options;
+mod retry;
+mod versioned;
+
+pub mod model;
+pub mod provider;
 pub mod tool;
 
+#[cfg(feature = "techne")]
+pub mod mcp;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+pub use boot::{BootConfig, CacheType, Executor, Pooling, Runtime, Stage};
+pub use cassette::Cassette;
+pub use connect::ConnectConfig;
 pub use error::Error;
+pub use image::Image;
+pub use interrupt::Interrupt;
+pub use model::Model;
+pub use monitor::{Metrics, MonitorEvent, Slot};
+pub use options::Options;
+pub use provider::Provider;
+pub use retry::Retry;
 pub use tool::Tool;
+pub use versioned::Versioned;
 
-use serde::Deserialize;
+use futures_util::future::{self, Either};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sipper::{FutureExt, Sipper, Straw, StreamExt, sipper};
+use tokio::fs;
 use tokio::io::{self, AsyncBufReadExt};
 use tokio::process;
+use tokio::sync::broadcast;
 use tokio::task;
 use tokio::time;
 
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::pin::pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
 
 pub use reqwest::IntoUrl;
 pub use url::Url;
@@ -23,7 +57,13 @@ pub use url::Url;
 #[derive(Debug, Clone)]
 pub struct Reason {
     name: String,
-    server: Arc<Server>,
+    server: Arc<Mutex<Server>>,
+    launch: Option<Arc<Launch>>,
+    options: Options,
+    retry: Retry,
+    provider: Arc<dyn Provider>,
+    cassette: Option<Cassette>,
+    client: reqwest::Client,
 }
 
 #[derive(Debug, Clone)]
@@ -38,34 +78,61 @@ impl Reason {
     const LLAMA_CPP_CONTAINER_ROCM: &'static str = "ghcr.io/hecrj/icebreaker:server-rocm-b4600";
 
     pub async fn connect(host: impl IntoUrl, model: &str) -> Result<Self, Error> {
+        Self::connect_with(host, model, ConnectConfig::default()).await
+    }
+
+    pub async fn connect_with(
+        host: impl IntoUrl,
+        model: &str,
+        config: ConnectConfig,
+    ) -> Result<Self, Error> {
         let host = host.into_url()?;
-        let client = reqwest::Client::new();
-
-        loop {
-            if client
-                .get(format!(
-                    "{host}/v1/models",
-                    host = host.as_str().trim_end_matches('/')
-                ))
-                .timeout(Duration::from_secs(5))
-                .send()
-                .await?
-                .error_for_status()
-                .is_ok()
-            {
-                break;
-            }
 
-            time::sleep(Duration::from_secs(1)).await;
-        }
+        let provider = config
+            .provider
+            .clone()
+            .unwrap_or_else(|| Arc::new(provider::OpenAiCompatible::LlamaCpp));
+
+        let client = config.client(provider.as_ref())?;
+
+        let _models = config
+            .retry
+            .send(
+                provider
+                    .models(&client, host.as_str().trim_end_matches('/'))
+                    .timeout(Duration::from_secs(5)),
+            )
+            .await?;
 
         Ok(Self {
             name: model.to_owned(),
-            server: Arc::new(Server::Remote(host)),
+            server: Arc::new(Mutex::new(Server::Remote(host))),
+            launch: None,
+            options: Options::default(),
+            retry: config.retry,
+            provider,
+            cassette: None,
+            client,
         })
     }
 
-    pub fn boot(model: impl AsRef<Path>, backend: Backend) -> impl Straw<Self, BootEvent, Error> {
+    pub fn replay(model: &str, cassette: Cassette) -> Result<Self, Error> {
+        Ok(Self {
+            name: model.to_owned(),
+            server: Arc::new(Mutex::new(Server::Remote(Url::parse(Cassette::HOST)?))),
+            launch: None,
+            options: Options::default(),
+            retry: Retry::never(),
+            provider: Arc::new(provider::OpenAiCompatible::LlamaCpp),
+            cassette: Some(cassette),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub fn boot(
+        model: impl Into<Model>,
+        config: impl Into<BootConfig>,
+    ) -> impl Straw<Self, BootEvent, Error> {
         #[derive(Clone)]
         struct Sender(sipper::Sender<BootEvent>);
 
@@ -74,178 +141,327 @@ impl Reason {
                 let _ = self.0.send(BootEvent::Logged(log)).await;
             }
 
-            async fn progress(&mut self, stage: &'static str, percent: u32) {
+            async fn progress(&mut self, stage: Stage, percent: u32) {
                 let _ = self.0.send(BootEvent::Progressed { stage, percent }).await;
             }
+
+            async fn track(&mut self, tracker: &mut boot::Tracker, log: String) {
+                let progress = tracker.track(&log);
+
+                self.log(log).await;
+
+                if let Some((stage, percent)) = progress {
+                    self.progress(stage, percent).await;
+                }
+            }
         }
 
         sipper(async move |sender| {
-            let model = model.as_ref().to_owned();
-            let model_file = model.file_stem().unwrap_or_default();
-            let name = model
-                .file_stem()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned();
+            let model = model.into();
+            let config = config.into();
+            let backend = config.backend;
+
+            let launch = Arc::new(Launch {
+                model: model.clone(),
+                config: config.clone(),
+            });
 
             let mut sender = Sender(sender);
-            sender.progress("Detecting executor...", 0).await;
 
-            let (server, stdout, stderr) = if let Ok(version) =
-                process::Command::new("llama-server")
-                    .arg("--version")
-                    .output()
-                    .await
-            {
-                sender
-                    .log("Local llama-server binary found!".to_owned())
-                    .await;
+            let cache = config.cache_dir.clone().unwrap_or_else(model::cache_dir);
 
-                let mut lines = version.stdout.lines();
+            let (model, projector) = {
+                let mut download = model.location.fetch(&cache, model.sha256.as_deref()).pin();
 
-                while let Some(line) = lines.next_line().await? {
-                    sender.log(line).await;
+                while let Some(percent) = download.sip().await {
+                    sender
+                        .progress(Stage::Downloading, boot::scale(percent, 0..=40))
+                        .await;
                 }
 
-                sender.progress("Launching assistant...", 99).await;
-
-                sender
-                    .log(format!(
-                        "Launching {model} with local llama-server...",
-                        model = model_file.display()
-                    ))
-                    .await;
+                let path = download.await?;
 
-                let mut server = Server::launch_with_executable("llama-server", &model, backend)?;
-                let stdout = server.stdout.take();
-                let stderr = server.stderr.take();
+                let projector = if let Some(projector) = &model.projector {
+                    let mut download = projector.fetch(&cache, None).pin();
 
-                (
-                    Server::Process {
-                        _handle: server,
-                        model,
-                    },
-                    stdout,
-                    stderr,
-                )
-            } else if let Ok(_docker) = process::Command::new("docker")
-                .arg("version")
-                .output()
-                .await
-            {
-                sender
-                    .log(format!(
-                        "Launching {model} with Docker...",
-                        model = model_file.display()
-                    ))
-                    .await;
-
-                sender.progress("Preparing container...", 0).await;
-
-                let volume = model.parent().unwrap_or(Path::new("."));
-
-                let command = match backend {
-                    Backend::Cpu => {
-                        format!(
-                            "create --rm -p {port}:80 -v {volume}:/models \
-                            {container} --jinja --model /models/{filename} \
-                            --port 80 --host 0.0.0.0",
-                            filename = model_file.display(),
-                            container = Self::LLAMA_CPP_CONTAINER_CPU,
-                            port = Server::PORT,
-                            volume = volume.display(),
-                        )
-                    }
-                    Backend::Cuda => {
-                        format!(
-                            "create --rm --gpus all -p {port}:80 -v {volume}:/models \
-                            {container} --jinja --model /models/{filename} \
-                            --port 80 --host 0.0.0.0 --gpu-layers 40",
-                            filename = model_file.display(),
-                            container = Self::LLAMA_CPP_CONTAINER_CUDA,
-                            port = Server::PORT,
-                            volume = volume.display(),
-                        )
-                    }
-                    Backend::Rocm => {
-                        format!(
-                            "create --rm -p {port}:80 -v {volume}:/models \
-                            --device=/dev/kfd --device=/dev/dri \
-                            --security-opt seccomp=unconfined --group-add video \
-                            {container} --model /models/{filename} \
-                            --port 80 --host 0.0.0.0 --gpu-layers 40",
-                            filename = model_file.display(),
-                            container = Self::LLAMA_CPP_CONTAINER_ROCM,
-                            port = Server::PORT,
-                            volume = volume.display(),
-                        )
+                    while let Some(percent) = download.sip().await {
+                        sender
+                            .progress(Stage::Downloading, boot::scale(percent, 40..=45))
+                            .await;
                     }
+
+                    Some(download.await?)
+                } else {
+                    None
                 };
 
-                let mut docker = process::Command::new("docker")
-                    .args(Server::parse_args(&command))
-                    .kill_on_drop(true)
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::piped())
-                    .spawn()?;
+                (path, projector)
+            };
+
+            let port = match config.port {
+                Some(port) => port,
+                None => Server::available_port()?,
+            };
 
-                let notify_progress = {
-                    let mut sender = sender.clone();
+            let model_file = model.file_stem().unwrap_or_default();
+            let model_filename = model.file_name().unwrap_or_default();
+            let name = model_file.to_string_lossy().into_owned();
 
-                    let output = io::BufReader::new(docker.stderr.take().expect("piped stderr"));
+            sender.progress(Stage::Detecting, 45).await;
 
-                    async move {
-                        let mut lines = output.lines();
+            let (logs, mut receiver) = broadcast::channel(Server::LOG_CAPACITY);
 
-                        while let Ok(Some(log)) = lines.next_line().await {
-                            sender.log(log).await;
+            let Some((executor, version)) = config.executor.detect().await else {
+                return Err(Error::NoExecutorAvailable);
+            };
+
+            let (mut server, stdout, stderr) = match executor {
+                Executor::LlamaServer => {
+                    sender
+                        .log("Local llama-server binary found!".to_owned())
+                        .await;
+
+                    let mut lines = version.lines();
+
+                    while let Some(line) = lines.next_line().await? {
+                        sender.log(line).await;
+                    }
+
+                    sender.progress(Stage::Launching, 62).await;
+
+                    sender
+                        .log(format!(
+                            "Launching {model} with local llama-server...",
+                            model = model_file.display()
+                        ))
+                        .await;
+
+                    let mut server = Server::launch_with_executable(
+                        "llama-server",
+                        &model,
+                        projector.as_deref(),
+                        port,
+                        &config,
+                    )?;
+                    let stdout = server.stdout.take();
+                    let stderr = server.stderr.take();
+
+                    (
+                        Server::Process {
+                            handle: server,
+                            model,
+                            port,
+                            logs: logs.downgrade(),
+                        },
+                        stdout,
+                        stderr,
+                    )
+                }
+                Executor::Container(runtime) => {
+                    sender
+                        .log(format!(
+                            "Launching {model} with {runtime}...",
+                            model = model_file.display()
+                        ))
+                        .await;
+
+                    sender.progress(Stage::Preparing, 60).await;
+
+                    let volume = model.parent().unwrap_or(Path::new("."));
+
+                    let (projector_volume, projector_flags) = projector
+                        .as_ref()
+                        .map(|projector| {
+                            (
+                                format!(
+                                    "-v {volume}:/projectors",
+                                    volume = projector.parent().unwrap_or(Path::new(".")).display(),
+                                ),
+                                format!(
+                                    "--mmproj /projectors/{filename}",
+                                    filename = projector.file_name().unwrap_or_default().display(),
+                                ),
+                            )
+                        })
+                        .unwrap_or_default();
+
+                    let command = match backend {
+                        Backend::Cpu => {
+                            format!(
+                                "create --rm -p {port}:80 -v {volume}:/models {projector_volume} \
+                                {container} --jinja --model /models/{filename} \
+                                --port 80 --host 0.0.0.0 {projector_flags}",
+                                filename = model_filename.display(),
+                                container = Self::LLAMA_CPP_CONTAINER_CPU,
+                                volume = volume.display(),
+                            )
+                        }
+                        Backend::Cuda => {
+                            format!(
+                                "create --rm {gpu_flags} -p {port}:80 -v {volume}:/models {projector_volume} \
+                                {container} --jinja --model /models/{filename} \
+                                --port 80 --host 0.0.0.0 {projector_flags}",
+                                filename = model_filename.display(),
+                                container = Self::LLAMA_CPP_CONTAINER_CUDA,
+                                gpu_flags = runtime.gpu_flags(),
+                                volume = volume.display(),
+                            )
+                        }
+                        Backend::Rocm => {
+                            format!(
+                                "create --rm -p {port}:80 -v {volume}:/models {projector_volume} \
+                                --device=/dev/kfd --device=/dev/dri \
+                                --security-opt seccomp=unconfined --group-add video \
+                                {container} --model /models/{filename} \
+                                --port 80 --host 0.0.0.0 {projector_flags}",
+                                filename = model_filename.display(),
+                                container = Self::LLAMA_CPP_CONTAINER_ROCM,
+                                volume = volume.display(),
+                            )
                         }
+                    };
+
+                    let mut create = process::Command::new(runtime.command())
+                        .args(Server::parse_args(&command))
+                        .args(config.server_args(40))
+                        .kill_on_drop(true)
+                        .stdout(std::process::Stdio::piped())
+                        .stderr(std::process::Stdio::piped())
+                        .spawn()?;
+
+                    let notify_progress = {
+                        let mut sender = sender.clone();
+
+                        let output =
+                            io::BufReader::new(create.stderr.take().expect("piped stderr"));
+
+                        async move {
+                            let mut lines = output.lines();
+                            let mut tracker = boot::Tracker::default();
+
+                            while let Ok(Some(log)) = lines.next_line().await {
+                                sender.track(&mut tracker, log).await;
+                            }
+                        }
+                    };
+
+                    let _handle = task::spawn(notify_progress);
+
+                    let container = {
+                        let output =
+                            io::BufReader::new(create.stdout.take().expect("piped stdout"));
+
+                        let mut lines = output.lines();
+
+                        lines.next_line().await?.ok_or_else(|| {
+                            Error::ContainerFailed("no container id returned by the runtime")
+                        })?
+                    };
+
+                    if !create.wait().await?.success() {
+                        return Err(Error::ContainerFailed("failed to create container"));
                     }
-                };
 
-                let _handle = task::spawn(notify_progress);
+                    sender.progress(Stage::Launching, 62).await;
 
-                let container = {
-                    let output = io::BufReader::new(docker.stdout.take().expect("piped stdout"));
+                    let server = Server::Container {
+                        runtime,
+                        id: container.clone(),
+                        model,
+                        port,
+                        logs: logs.downgrade(),
+                    };
 
-                    let mut lines = output.lines();
+                    let _start = process::Command::new(runtime.command())
+                        .args(["start", &container])
+                        .output()
+                        .await?;
 
-                    lines
-                        .next_line()
-                        .await?
-                        .ok_or_else(|| Error::DockerFailed("no container id returned by docker"))?
-                };
+                    let mut logs = process::Command::new(runtime.command())
+                        .args(["logs", "-f", &container])
+                        .stdout(std::process::Stdio::piped())
+                        .stderr(std::process::Stdio::piped())
+                        .spawn()?;
 
-                if !docker.wait().await?.success() {
-                    return Err(Error::DockerFailed("failed to create container"));
+                    (server, logs.stdout.take(), logs.stderr.take())
                 }
+                Executor::Ollama => {
+                    let unsupported = [
+                        (projector.is_some(), "projector models"),
+                        (config.embedding.is_some(), "embeddings"),
+                        (config.chat_template.is_some(), "custom chat templates"),
+                        (config.metrics, "metrics"),
+                        (
+                            config.cache_type_k != config.cache_type_v,
+                            "different cache types for keys and values",
+                        ),
+                        (!config.extra_args.is_empty(), "extra arguments"),
+                    ];
+
+                    if let Some((_, option)) = unsupported.into_iter().find(|(is_set, _)| *is_set) {
+                        return Err(Error::Unsupported {
+                            provider: "Ollama",
+                            feature: option,
+                        });
+                    }
 
-                sender.progress("Launching assistant...", 99).await;
+                    sender
+                        .log(format!(
+                            "Launching {model} with Ollama...",
+                            model = model_file.display()
+                        ))
+                        .await;
 
-                let server = Server::Container {
-                    id: container.clone(),
-                    model,
-                };
+                    let mut lines = version.lines();
 
-                let _start = process::Command::new("docker")
-                    .args(["start", &container])
-                    .output()
-                    .await?;
+                    while let Some(line) = lines.next_line().await? {
+                        sender.log(line).await;
+                    }
 
-                let mut logs = process::Command::new("docker")
-                    .args(["logs", "-f", &container])
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::piped())
-                    .spawn()?;
+                    sender.progress(Stage::Launching, 62).await;
 
-                (server, logs.stdout.take(), logs.stderr.take())
-            } else {
-                return Err(Error::NoExecutorAvailable);
-            };
+                    let mut command = process::Command::new("ollama");
 
-            let log_output = {
-                let mut sender = sender.clone();
+                    let _ = command
+                        .arg("serve")
+                        .env("OLLAMA_HOST", format!("127.0.0.1:{port}"));
+
+                    if config.flash_attention {
+                        let _ = command.env("OLLAMA_FLASH_ATTENTION", "1");
+                    }
+
+                    if let Some(parallel) = config.parallel {
+                        let _ = command.env("OLLAMA_NUM_PARALLEL", parallel.to_string());
+                    }
+
+                    if let Some(cache_type) = config.cache_type_k {
+                        let _ = command.env("OLLAMA_KV_CACHE_TYPE", cache_type.to_string());
+                    }
+
+                    let mut server = command
+                        .kill_on_drop(true)
+                        .stdout(std::process::Stdio::piped())
+                        .stderr(std::process::Stdio::piped())
+                        .spawn()?;
+
+                    let stdout = server.stdout.take();
+                    let stderr = server.stderr.take();
+
+                    (
+                        Server::Process {
+                            handle: server,
+                            model,
+                            port,
+                            logs: logs.downgrade(),
+                        },
+                        stdout,
+                        stderr,
+                    )
+                }
+                Executor::Auto => unreachable!("executors are detected before launching"),
+            };
 
+            let _log_handle = {
                 let mut lines = {
                     use futures_util::stream;
                     use tokio_stream::wrappers::LinesStream;
@@ -262,26 +478,64 @@ impl Reason {
                     )
                 };
 
-                async move {
+                task::spawn(async move {
                     while let Some(line) = lines.next().await {
                         if let Ok(log) = line {
-                            sender.log(log).await;
+                            let _ = logs.send(log);
+                        }
+                    }
+                })
+            };
+
+            let log_output = {
+                let mut sender = sender.clone();
+
+                async move {
+                    const TAIL: usize = 20;
+
+                    let mut tracker = boot::Tracker::default();
+                    let mut tail = VecDeque::with_capacity(TAIL);
+
+                    loop {
+                        let log = match receiver.recv().await {
+                            Ok(log) => log,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+
+                        if tail.len() == TAIL {
+                            let _ = tail.pop_front();
                         }
+
+                        tail.push_back(log.clone());
+                        sender.track(&mut tracker, log).await;
                     }
 
-                    false
+                    Vec::from(tail)
                 }
                 .boxed()
             };
 
+            let client = reqwest::Client::new();
+
+            let provider: Arc<dyn Provider> = match executor {
+                Executor::Ollama => Arc::new(provider::Ollama::default()),
+                Executor::LlamaServer | Executor::Container(_) | Executor::Auto => {
+                    Arc::new(provider::OpenAiCompatible::LlamaCpp)
+                }
+            };
+
             let check_health = {
                 let address = server.host();
+                let health = provider.health(&client, &address);
 
                 async move {
+                    // Booting may take minutes; process exits and the boot timeout bound the wait
                     loop {
                         time::sleep(Duration::from_secs(1)).await;
 
-                        if let Ok(response) = reqwest::get(format!("{address}/health")).await
+                        if let Some(health) = health.try_clone()
+                            && let Ok(response) = health.send().await
                             && response.error_for_status().is_ok()
                         {
                             return true;
@@ -291,85 +545,292 @@ impl Reason {
                 .boxed()
             };
 
-            let log_handle = task::spawn(log_output);
+            let boot = future::select(check_health, log_output);
+
+            let result = match config.timeout {
+                Some(timeout) => time::timeout(timeout, boot)
+                    .await
+                    .map_err(|_| Error::BootTimedOut(timeout))?,
+                None => boot.await,
+            };
+
+            match result {
+                Either::Left(_) => {
+                    let name = if executor == Executor::Ollama {
+                        sender.progress(Stage::LoadingModel, 65).await;
 
-            if check_health.await {
-                log_handle.abort();
+                        let name = server.create_ollama_model(&name, &config, &cache).await?;
 
-                return Ok(Self {
-                    name,
-                    server: Arc::new(server),
-                });
-            }
+                        sender.progress(Stage::WarmingUp, 94).await;
 
-            Err(Error::ExecutorFailed("llama-server exited unexpectedly"))
+                        let _preload = client
+                            .post(format!("{host}/api/generate", host = server.host()))
+                            .json(&json!({ "model": name }))
+                            .send()
+                            .await?
+                            .error_for_status()?;
+
+                        name
+                    } else {
+                        name
+                    };
+
+                    sender.progress(Stage::Ready, 100).await;
+
+                    Ok(Self {
+                        name,
+                        server: Arc::new(Mutex::new(server)),
+                        launch: Some(launch),
+                        options: Options::default(),
+                        retry: config.retry,
+                        provider,
+                        cassette: None,
+                        client,
+                    })
+                }
+                Either::Right((mut logs, _)) => {
+                    let (program, reason) = match executor {
+                        Executor::Ollama => ("ollama", "ollama exited unexpectedly"),
+                        Executor::Container(_) => ("container", "container exited unexpectedly"),
+                        Executor::LlamaServer | Executor::Auto => {
+                            ("llama-server", "llama-server exited unexpectedly")
+                        }
+                    };
+
+                    if let Server::Process { handle, .. } = &mut server
+                        && let Ok(Some(status)) = handle.try_wait()
+                    {
+                        logs.push(format!("{program} {status}"));
+                    }
+
+                    Err(Error::ExecutorFailed { reason, logs })
+                }
+            }
         })
     }
 
+    pub fn with_options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    pub fn with_cassette(self, cassette: Cassette) -> Self {
+        Self {
+            cassette: Some(cassette),
+            ..self
+        }
+    }
+
+    pub fn with_provider(self, provider: impl Provider + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            ..self
+        }
+    }
+
     pub fn reply(
         &self,
         messages: &[Message],
         append: &[Message],
         tools: &[Tool],
+    ) -> impl Straw<Reply, Event, Error> {
+        self.reply_with(messages, append, tools, Options::default())
+    }
+
+    pub fn reply_with(
+        &self,
+        messages: &[Message],
+        append: &[Message],
+        tools: &[Tool],
+        options: Options,
     ) -> impl Straw<Reply, Event, Error> {
         sipper(move |mut progress| async move {
-            let mut completion = self.complete(messages, append, tools).pin();
+            let interrupt = options.or(&self.options).interrupt;
+
+            let mut completion = self.complete_with(messages, append, tools, options).pin();
             let mut reply = Reply {
                 outputs: Vec::new(),
+                usage: None,
+                finish_reason: None,
+                interrupted: false,
             };
 
-            while let Some(event) = completion.sip().await {
-                reply.update(&event);
-                progress.send(event).await;
+            let mut interrupted = pin!(async {
+                match &interrupt {
+                    Some(interrupt) => interrupt.interrupted().await,
+                    None => future::pending().await,
+                }
+            });
+
+            loop {
+                match future::select(completion.sip(), interrupted.as_mut()).await {
+                    Either::Left((Some(event), _)) => {
+                        reply.update(&event);
+                        progress.send(event).await;
+                    }
+                    Either::Left((None, _)) => break,
+                    Either::Right(_) => {
+                        reply.interrupted = true;
+
+                        return Ok(reply);
+                    }
+                }
             }
 
+            completion.await?;
+
             Ok(reply)
         })
     }
 
-    pub fn complete(
+    pub fn extract<T: Structured>(&self, messages: &[Message]) -> impl Straw<T, Event, Error> {
+        sipper(move |mut sender| async move {
+            let mut reply = self
+                .reply_with(messages, &[], &[], Options::default().schema(T::schema()))
+                .pin();
+
+            while let Some(event) = reply.sip().await {
+                sender.send(event).await;
+            }
+
+            let reply = reply.await?;
+
+            let output = reply
+                .outputs
+                .iter()
+                .rev()
+                .find_map(|output| match output {
+                    Output::Message(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+
+            serde_json::from_str(output).map_err(|error| Error::SchemaMismatched(Arc::new(error)))
+        })
+    }
+
+    pub fn run<H: tool::Handler>(
         &self,
         messages: &[Message],
-        append: &[Message],
         tools: &[Tool],
-    ) -> impl Straw<(), Event, Error> {
+        handler: &mut H,
+        max_iterations: usize,
+    ) -> impl Straw<Vec<Message>, RunEvent, Error> {
         sipper(move |mut sender| async move {
-            let client = reqwest::Client::new();
+            let mut appended = Vec::new();
+
+            for _ in 0..max_iterations {
+                let mut reply = self.reply(messages, &appended, tools).pin();
 
-            let request = {
-                let messages: Vec<_> = messages
+                while let Some(event) = reply.sip().await {
+                    sender.send(RunEvent::Replied(event)).await;
+                }
+
+                let reply = reply.await?;
+
+                let calls: Vec<_> = reply
+                    .outputs
                     .iter()
-                    .chain(append)
-                    .map(Message::to_json)
+                    .filter_map(|output| match output {
+                        Output::ToolCalls(calls) => Some(calls.clone()),
+                        _ => None,
+                    })
+                    .flatten()
                     .collect();
 
-                client
-                    .post(format!(
-                        "{host}/v1/chat/completions",
-                        host = self.server.host(),
-                    ))
-                    .json(&json!({
-                        "model": self.name,
-                        "messages": messages,
-                        "tools": tools,
-                        "stream": true,
-                        "cache_prompt": true,
-                    }))
-            };
+                appended.extend(reply.outputs.into_iter().map(Message::Assistant));
 
-            let mut response = request.send().await?.error_for_status()?;
-            let mut buffer = Vec::new();
+                if calls.is_empty() || reply.interrupted {
+                    return Ok(appended);
+                }
+
+                for call in calls {
+                    sender.send(RunEvent::ToolCalled(call.clone())).await;
 
-            enum Mode {
-                Reasoning,
-                Messaging,
-                ToolCalling,
+                    // Failed calls are reported back so the model can recover
+                    let content = match handler.call(&call).await {
+                        Ok(content) => content,
+                        Err(error) => error.to_string(),
+                    };
+
+                    let response = tool::Response {
+                        id: call.id().clone(),
+                        content,
+                    };
+
+                    sender.send(RunEvent::ToolResponded(response.clone())).await;
+                    appended.push(Message::Tool(response));
+                }
             }
 
-            let mut mode = None;
-            let mut mode_started_at = Instant::now();
+            Err(Error::IterationLimitReached {
+                iterations: max_iterations,
+                messages: appended,
+            })
+        })
+    }
+
+    pub fn complete(
+        &self,
+        messages: &[Message],
+        append: &[Message],
+        tools: &[Tool],
+    ) -> impl Straw<(), Event, Error> {
+        self.complete_with(messages, append, tools, Options::default())
+    }
+
+    pub fn complete_with(
+        &self,
+        messages: &[Message],
+        append: &[Message],
+        tools: &[Tool],
+        options: Options,
+    ) -> impl Straw<(), Event, Error> {
+        sipper(move |mut sender| async move {
+            let options = options.or(&self.options);
+
+            let request = self.provider.chat(
+                &self.client,
+                &self.server().host(),
+                provider::Request {
+                    model: &self.name,
+                    messages,
+                    append,
+                    tools,
+                    options: &options,
+                },
+            )?;
+
+            let (client, request) = request.build_split();
+            let request = request?;
+
+            let body = request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .unwrap_or_default()
+                .to_vec();
+
+            let mut chunks = match &self.cassette {
+                Some(cassette) if cassette.is_replaying() => {
+                    cassette::Chunks::Replay(cassette.play(&body).await?.into_iter())
+                }
+                _ => cassette::Chunks::Live(
+                    self.retry
+                        .send(reqwest::RequestBuilder::from_parts(client, request))
+                        .await?,
+                ),
+            };
+
+            let mut recording = Vec::new();
+            let mut decoder = self.provider.decoder();
+            let mut buffer = Vec::new();
+
+            while let Some(chunk) = chunks.next().await? {
+                if let Some(cassette) = &self.cassette
+                    && cassette.is_recording()
+                {
+                    recording.push(chunk.clone());
+                }
 
-            while let Some(chunk) = response.chunk().await? {
                 buffer.extend(chunk);
 
                 let mut lines = buffer
@@ -383,152 +844,268 @@ impl Reason {
                 };
 
                 for line in lines {
-                    #[derive(Deserialize)]
-                    struct Data {
-                        choices: Vec<Choice>,
+                    for event in decoder.decode(line)? {
+                        sender.send(event).await;
                     }
+                }
 
-                    #[derive(Deserialize)]
-                    struct Choice {
-                        delta: Delta,
-                    }
+                buffer = last_line.to_vec();
+            }
 
-                    #[derive(Deserialize)]
-                    #[serde(untagged)]
-                    enum Delta {
-                        Text { content: String },
-                        Call { tool_calls: [ToolCall; 1] },
-                    }
+            if let Some(cassette) = &self.cassette
+                && cassette.is_recording()
+            {
+                cassette.store(&body, &recording).await?;
+            }
 
-                    #[derive(Deserialize)]
-                    #[serde(untagged)]
-                    enum ToolCall {
-                        New { id: tool::Id, function: Function },
-                        Update { function: FunctionUpdate },
-                    }
+            Ok(())
+        })
+    }
 
-                    #[derive(Deserialize)]
-                    struct Function {
-                        name: String,
-                        arguments: String,
-                    }
+    pub async fn embed(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            data: Vec<Embedding>,
+        }
 
-                    #[derive(Deserialize)]
-                    struct FunctionUpdate {
-                        arguments: String,
-                    }
+        #[derive(Deserialize)]
+        struct Embedding {
+            index: usize,
+            embedding: Vec<f32>,
+        }
+
+        let mut response: Response = self
+            .retry
+            .send(
+                self.client
+                    .post(format!("{host}/v1/embeddings", host = self.server().host()))
+                    .json(&json!({
+                        "model": self.name,
+                        "input": inputs,
+                    })),
+            )
+            .await?
+            .json()
+            .await?;
+
+        response.data.sort_by_key(|embedding| embedding.index);
+
+        Ok(response
+            .data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn host(&self) -> String {
+        self.server().host()
+    }
 
-                    const PREFIX: usize = b"data:".len();
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
 
-                    if line.len() < PREFIX {
-                        continue;
+    pub fn source(&self) -> Source {
+        match &*self.server() {
+            Server::Container { model, .. }
+            | Server::Process { model, .. }
+            | Server::Stopped { model, .. } => Source::Local(model.clone()),
+            Server::Remote(url) => Source::Remote(url.clone()),
+        }
+    }
+
+    pub async fn status(&self) -> Status {
+        let host = {
+            let mut server = self.server();
+
+            match &mut *server {
+                Server::Stopped { .. } => return Status::Stopped,
+                Server::Process { handle, .. } => {
+                    if let Ok(Some(status)) = handle.try_wait() {
+                        return Status::Exited(status.code());
                     }
+                }
+                Server::Container { .. } | Server::Remote(_) => {}
+            }
 
-                    let Ok(data): Result<Data, _> = serde_json::from_slice(&line[PREFIX..]) else {
-                        continue;
-                    };
+            server.host()
+        };
 
-                    let Some(choice) = data.choices.first() else {
-                        continue;
-                    };
+        match self
+            .provider
+            .health(&self.client, &host)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => Status::Ready,
+            Ok(response) if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                Status::Loading
+            }
+            Ok(_) => Status::Unhealthy,
+            Err(_) => Status::Unreachable,
+        }
+    }
 
-                    match &choice.delta {
-                        Delta::Text { content } => {
-                            match mode {
-                                None | Some(Mode::Messaging) if content.contains("<think>") => {
-                                    mode = Some(Mode::Reasoning);
-                                    mode_started_at = Instant::now();
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        let grace_period = self
+            .launch
+            .as_ref()
+            .map(|launch| launch.config.grace_period)
+            .unwrap_or(BootConfig::GRACE_PERIOD);
 
-                                    sender
-                                        .send(Event::OutputAdded {
-                                            output: Output::Reasoning(Reasoning::default()),
-                                        })
-                                        .await;
+        let server = {
+            let mut server = self.server();
+            let stopped = server.stopped();
 
-                                    continue;
-                                }
-                                Some(Mode::Reasoning) if content.contains("</think>") => {
-                                    mode = Some(Mode::Messaging);
-                                    mode_started_at = Instant::now();
+            std::mem::replace(&mut *server, stopped)
+        };
 
-                                    continue;
-                                }
-                                None => {
-                                    mode = Some(Mode::Messaging);
-                                    mode_started_at = Instant::now();
-
-                                    sender
-                                        .send(Event::OutputAdded {
-                                            output: Output::Message(String::new()),
-                                        })
-                                        .await;
-                                }
-                                _ => {}
-                            }
+        server.shutdown(grace_period).await
+    }
 
-                            if let Some(Mode::Reasoning | Mode::Messaging) = mode {
-                                let _ = sender
-                                    .send(Event::TextChanged {
-                                        delta: content.clone(),
-                                        duration: mode_started_at.elapsed(),
-                                    })
-                                    .await;
-                            }
-                        }
-                        Delta::Call { tool_calls } => {
-                            if !matches!(mode, Some(Mode::ToolCalling)) {
-                                mode = Some(Mode::ToolCalling);
-                                mode_started_at = Instant::now();
-
-                                sender
-                                    .send(Event::OutputAdded {
-                                        output: Output::ToolCalls(Vec::new()),
-                                    })
-                                    .await;
-                            }
+    pub fn restart(&self) -> impl Straw<(), BootEvent, Error> {
+        sipper(async move |sender| {
+            let Some(launch) = &self.launch else {
+                return Err(Error::RestartUnsupported);
+            };
 
-                            match &tool_calls[0] {
-                                ToolCall::New { id, function } => {
-                                    sender
-                                        .send(Event::ToolCallAdded {
-                                            id: id.clone(),
-                                            name: function.name.clone(),
-                                            arguments: function.arguments.clone(),
-                                        })
-                                        .await;
-                                }
-                                ToolCall::Update { function } => {
-                                    sender
-                                        .send(Event::ArgumentsChanged {
-                                            delta: function.arguments.clone(),
-                                            duration: mode_started_at.elapsed(),
-                                        })
-                                        .await;
-                                }
-                            }
+            self.shutdown().await?;
+
+            let reason = Self::boot(launch.model.clone(), launch.config.clone())
+                .run(&sender)
+                .await?;
+
+            let server = {
+                let mut server = reason.server();
+                let stopped = server.stopped();
+
+                std::mem::replace(&mut *server, stopped)
+            };
+
+            *self.server() = server;
+
+            Ok(())
+        })
+    }
+
+    pub fn monitor(&self, interval: Duration) -> impl Sipper<(), MonitorEvent> + use<> {
+        let reason = self.clone();
+
+        sipper(async move |mut sender| {
+            let mut logs = None;
+            let mut status = None;
+            let mut next_poll = time::Instant::now();
+
+            loop {
+                if logs.is_none() {
+                    logs = reason.server().logs();
+                }
+
+                let log = {
+                    let next_log = async {
+                        match &mut logs {
+                            Some(logs) => Some(logs.recv().await),
+                            None => future::pending().await,
                         }
+                    };
+
+                    match future::select(pin!(next_log), pin!(time::sleep_until(next_poll))).await {
+                        Either::Left((log, _)) => log,
+                        Either::Right(_) => None,
+                    }
+                };
+
+                match log {
+                    Some(Ok(log)) => {
+                        sender.send(MonitorEvent::Logged(log)).await;
+                    }
+                    Some(Err(broadcast::error::RecvError::Lagged(_))) | None => {}
+                    Some(Err(broadcast::error::RecvError::Closed)) => {
+                        logs = None;
                     }
                 }
 
-                buffer = last_line.to_vec();
-            }
+                // A steady stream of logs must not delay polling past its deadline
+                if time::Instant::now() < next_poll {
+                    continue;
+                }
 
-            Ok(())
+                let current = reason.status().await;
+
+                if status != Some(current) {
+                    status = Some(current);
+                    sender.send(MonitorEvent::StatusChanged(current)).await;
+                }
+
+                if current == Status::Ready {
+                    if let Ok(slots) = reason.slots().await {
+                        sender.send(MonitorEvent::SlotsReported(slots)).await;
+                    }
+
+                    if let Ok(metrics) = reason.metrics().await {
+                        sender.send(MonitorEvent::MetricsReported(metrics)).await;
+                    }
+                }
+
+                next_poll = time::Instant::now() + interval;
+            }
         })
     }
 
-    pub fn name(&self) -> &str {
-        &self.name
+    pub async fn slots(&self) -> Result<Vec<Slot>, Error> {
+        let host = self.host();
+
+        Ok(self
+            .client
+            .get(format!("{host}/slots"))
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
     }
 
-    pub fn source(&self) -> Source {
-        match self.server.as_ref() {
-            Server::Container { model, .. } | Server::Process { model, .. } => {
-                Source::Local(model.clone())
-            }
-            Server::Remote(url) => Source::Remote(url.clone()),
-        }
+    pub async fn metrics(&self) -> Result<Metrics, Error> {
+        let host = self.host();
+
+        let metrics = self
+            .client
+            .get(format!("{host}/metrics"))
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(Metrics::parse(&metrics))
     }
+
+    fn server(&self) -> MutexGuard<'_, Server> {
+        self.server.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Debug)]
+struct Launch {
+    model: Model,
+    config: BootConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ready,
+    Loading,
+    Unhealthy,
+    Unreachable,
+    Exited(Option<i32>),
+    Stopped,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -557,14 +1134,23 @@ impl Backend {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", content = "content", rename_all = "snake_case")]
 pub enum Message {
     System(String),
     Assistant(Output),
     User(String),
+    Multimodal(Vec<Part>),
     Tool(tool::Response),
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content", rename_all = "snake_case")]
+pub enum Part {
+    Text(String),
+    Image(Image),
+}
+
 impl Message {
     pub fn system(prompt: impl AsRef<str>) -> Self {
         Self::System(prompt.as_ref().to_owned())
@@ -574,6 +1160,14 @@ impl Message {
         Self::User(prompt.as_ref().to_owned())
     }
 
+    pub fn multimodal(prompt: impl AsRef<str>, images: impl IntoIterator<Item = Image>) -> Self {
+        Self::Multimodal(
+            std::iter::once(Part::Text(prompt.as_ref().to_owned()))
+                .chain(images.into_iter().map(Part::Image))
+                .collect(),
+        )
+    }
+
     pub fn to_json(&self) -> serde_json::Value {
         match self {
             Self::System(content) => json!({
@@ -618,6 +1212,28 @@ impl Message {
                 "role": "user",
                 "content": content,
             }),
+            Self::Multimodal(parts) => {
+                let content: Vec<_> = parts
+                    .iter()
+                    .map(|part| match part {
+                        Part::Text(text) => json!({
+                            "type": "text",
+                            "text": text,
+                        }),
+                        Part::Image(image) => json!({
+                            "type": "image_url",
+                            "image_url": {
+                                "url": image.to_url(),
+                            },
+                        }),
+                    })
+                    .collect();
+
+                json!({
+                    "role": "user",
+                    "content": content,
+                })
+            }
             Self::Tool(response) => json!({
                 "role": "tool",
                 "tool_call_id": response.id,
@@ -627,9 +1243,12 @@ impl Message {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Reply {
     pub outputs: Vec<Output>,
+    pub usage: Option<Usage>,
+    pub finish_reason: Option<FinishReason>,
+    pub interrupted: bool,
 }
 
 impl Reply {
@@ -649,6 +1268,7 @@ impl Reply {
                 None | Some(Output::ToolCalls(_)) => {}
             },
             Event::ToolCallAdded {
+                index,
                 id,
                 name,
                 arguments,
@@ -657,28 +1277,50 @@ impl Reply {
                     return;
                 };
 
-                calls.push(tool::Call::Function {
-                    id: id.clone(),
-                    name: name.clone(),
-                    arguments: arguments.clone(),
-                });
+                if let Some(tool::Call::Function {
+                    arguments: existing,
+                    ..
+                }) = calls.get_mut(*index)
+                {
+                    existing.push_str(arguments);
+                } else {
+                    calls.push(tool::Call::Function {
+                        id: id.clone(),
+                        name: name.clone(),
+                        arguments: arguments.clone(),
+                    });
+                }
             }
-            Event::ArgumentsChanged { delta, .. } => {
+            Event::ArgumentsChanged { index, delta, .. } => {
                 let Some(Output::ToolCalls(calls)) = self.outputs.last_mut() else {
                     return;
                 };
 
-                let Some(tool::Call::Function { arguments, .. }) = calls.last_mut() else {
+                let Some(tool::Call::Function { arguments, .. }) = calls.get_mut(*index) else {
                     return;
                 };
 
                 arguments.push_str(delta);
             }
+            Event::ReasoningSigned { signature } => {
+                let Some(Output::Reasoning(reasoning)) = self.outputs.last_mut() else {
+                    return;
+                };
+
+                reasoning.signature = Some(signature.clone());
+            }
+            Event::UsageReported { usage } => {
+                self.usage = Some(usage.clone());
+            }
+            Event::Finished { reason } => {
+                self.finish_reason = Some(reason.clone());
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content", rename_all = "snake_case")]
 pub enum Output {
     Reasoning(Reasoning),
     Message(String),
@@ -695,13 +1337,49 @@ impl Output {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+pub trait Structured: DeserializeOwned {
+    fn schema() -> tool::Schema;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Reasoning {
     pub text: String,
     pub duration: Duration,
+    #[serde(default)]
+    pub signature: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cached_tokens: u64,
+    pub timings: Option<Timings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    pub prompt_tokens: u64,
+    pub prompt_duration: Duration,
+    pub prompt_per_second: f64,
+    pub predicted_tokens: u64,
+    pub predicted_duration: Duration,
+    pub predicted_per_second: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum FinishReason {
+    Stop,
+    StopSequence(String),
+    Length,
+    ToolCalls,
+    ContentFilter,
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     OutputAdded {
         output: Output,
@@ -711,14 +1389,25 @@ pub enum Event {
         duration: Duration,
     },
     ToolCallAdded {
+        index: usize,
         id: tool::Id,
         name: String,
         arguments: String,
     },
     ArgumentsChanged {
+        index: usize,
         delta: String,
         duration: Duration,
     },
+    ReasoningSigned {
+        signature: String,
+    },
+    UsageReported {
+        usage: Usage,
+    },
+    Finished {
+        reason: FinishReason,
+    },
 }
 
 impl Event {
@@ -728,6 +1417,25 @@ impl Event {
             Event::TextChanged { delta, .. } => Some(delta),
             Event::ToolCallAdded { .. } => None,
             Event::ArgumentsChanged { .. } => None,
+            Event::ReasoningSigned { .. } => None,
+            Event::UsageReported { .. } => None,
+            Event::Finished { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RunEvent {
+    Replied(Event),
+    ToolCalled(tool::Call),
+    ToolResponded(tool::Response),
+}
+
+impl RunEvent {
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            RunEvent::Replied(event) => event.text(),
+            RunEvent::ToolCalled(_) | RunEvent::ToolResponded(_) => None,
         }
     }
 }
@@ -735,35 +1443,45 @@ impl Event {
 #[derive(Debug)]
 enum Server {
     Container {
+        runtime: Runtime,
         id: String,
         model: PathBuf,
+        port: u16,
+        logs: broadcast::WeakSender<String>,
     },
     Process {
-        _handle: process::Child,
+        handle: process::Child,
         model: PathBuf,
+        port: u16,
+        logs: broadcast::WeakSender<String>,
     },
     Remote(Url),
+    Stopped {
+        model: PathBuf,
+        port: u16,
+    },
 }
 
 impl Server {
-    const PORT: u64 = 8080;
+    const LOG_CAPACITY: usize = 1024;
 
     fn launch_with_executable(
         executable: &'static str,
         model: impl AsRef<Path>,
-        backend: Backend,
+        projector: Option<&Path>,
+        port: u16,
+        config: &BootConfig,
     ) -> Result<process::Child, Error> {
-        let gpu_flags = match backend {
-            Backend::Cpu => "",
-            Backend::Cuda | Backend::Rocm => "--gpu-layers 80",
-        };
+        let projector_flags = projector
+            .map(|projector| format!("--mmproj {projector}", projector = projector.display()))
+            .unwrap_or_default();
 
         let server = process::Command::new(executable)
             .args(Self::parse_args(&format!(
-                "--jinja --model {model} --port {port} --host 127.0.0.1 {gpu_flags}",
-                port = Self::PORT,
+                "--jinja --model {model} --port {port} --host 127.0.0.1 {projector_flags}",
                 model = model.as_ref().display()
             )))
+            .args(config.server_args(80))
             .kill_on_drop(true)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -772,6 +1490,66 @@ impl Server {
         Ok(server)
     }
 
+    async fn create_ollama_model(
+        &self,
+        name: &str,
+        config: &BootConfig,
+        cache: &Path,
+    ) -> Result<String, Error> {
+        let (Server::Process { model, port, .. } | Server::Container { model, port, .. }) = self
+        else {
+            unreachable!("ollama models are only created for local servers");
+        };
+
+        let name = name.to_lowercase();
+
+        let modelfile = {
+            let mut modelfile = format!("FROM {model}\n", model = model.display());
+
+            let mut parameter = |key: &str, value: Option<u32>| {
+                if let Some(value) = value {
+                    modelfile.push_str(&format!("PARAMETER {key} {value}\n"));
+                }
+            };
+
+            parameter("num_ctx", config.context_size);
+            parameter("num_thread", config.threads);
+            parameter("num_batch", config.batch_size);
+            parameter("num_gpu", config.gpu_layers);
+
+            modelfile
+        };
+
+        let path = cache.join("ollama").join(format!("{name}.Modelfile"));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(&path, modelfile).await?;
+
+        let output = process::Command::new("ollama")
+            .arg("create")
+            .arg(&name)
+            .arg("-f")
+            .arg(&path)
+            .env("OLLAMA_HOST", format!("127.0.0.1:{port}"))
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(Error::ExecutorFailed {
+                reason: "failed to create Ollama model",
+                logs: String::from_utf8_lossy(&output.stderr)
+                    .lines()
+                    .map(str::to_owned)
+                    .collect(),
+            });
+        }
+
+        Ok(name)
+    }
+
     fn parse_args(command: &str) -> impl Iterator<Item = &str> {
         command
             .split(' ')
@@ -779,14 +1557,81 @@ impl Server {
             .filter(|arg| !arg.is_empty())
     }
 
+    fn available_port() -> Result<u16, Error> {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+
+        Ok(listener.local_addr()?.port())
+    }
+
     fn host(&self) -> String {
         match self {
-            Server::Container { .. } | Server::Process { .. } => {
-                format!("http://localhost:{port}", port = Self::PORT)
+            Server::Container { port, .. }
+            | Server::Process { port, .. }
+            | Server::Stopped { port, .. } => {
+                format!("http://localhost:{port}")
             }
             Server::Remote(url) => url.as_str().trim_end_matches("/").to_owned(),
         }
     }
+
+    fn logs(&self) -> Option<broadcast::Receiver<String>> {
+        match self {
+            Server::Container { logs, .. } | Server::Process { logs, .. } => {
+                logs.upgrade().map(|logs| logs.subscribe())
+            }
+            Server::Remote(_) | Server::Stopped { .. } => None,
+        }
+    }
+
+    fn stopped(&self) -> Self {
+        match self {
+            Server::Container { model, port, .. }
+            | Server::Process { model, port, .. }
+            | Server::Stopped { model, port } => Server::Stopped {
+                model: model.clone(),
+                port: *port,
+            },
+            Server::Remote(url) => Server::Remote(url.clone()),
+        }
+    }
+
+    async fn shutdown(mut self, grace_period: Duration) -> Result<(), Error> {
+        match &mut self {
+            Server::Container { runtime, id, .. } => {
+                let id = std::mem::take(id);
+
+                let _stop = process::Command::new(runtime.command())
+                    .args(["stop", "--time"])
+                    .arg(grace_period.as_secs().to_string())
+                    .arg(&id)
+                    .output()
+                    .await?;
+            }
+            Server::Process { handle, .. } => {
+                if handle.try_wait()?.is_some() {
+                    return Ok(());
+                }
+
+                #[cfg(unix)]
+                {
+                    if let Some(pid) = handle.id() {
+                        // SAFETY: the child has not been reaped yet, so the pid still refers to it
+                        let _ = unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+                    }
+
+                    if time::timeout(grace_period, handle.wait()).await.is_err() {
+                        handle.kill().await?;
+                    }
+                }
+
+                #[cfg(not(unix))]
+                handle.kill().await?;
+            }
+            Server::Remote(_) | Server::Stopped { .. } => {}
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for Server {
@@ -794,22 +1639,24 @@ impl Drop for Server {
         use std::process;
 
         match self {
-            Self::Container { id, .. } => {
-                let _ = process::Command::new("docker")
+            Self::Container { runtime, id, .. } if !id.is_empty() => {
+                let _ = process::Command::new(runtime.command())
                     .args(["stop", id])
                     .stdin(process::Stdio::null())
                     .stdout(process::Stdio::null())
                     .stderr(process::Stdio::null())
                     .spawn();
             }
+            Self::Container { .. } => {}
             Self::Process { .. } => {}
             Self::Remote(_url) => {}
+            Self::Stopped { .. } => {}
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum BootEvent {
-    Progressed { stage: &'static str, percent: u32 },
+    Progressed { stage: Stage, percent: u32 },
     Logged(String),
 }
@@ -0,0 +1,93 @@
+use crate::Error;
+use crate::tool::{self, Tool};
+
+use techne_client::Client;
+use techne_mcp as mcp;
+
+use mcp::server::Content;
+use mcp::server::content::Unstructured;
+
+#[derive(Debug, Default)]
+pub struct Toolbox {
+    servers: Vec<Server>,
+}
+
+#[derive(Debug)]
+struct Server {
+    client: Client,
+    tools: Vec<Tool>,
+}
+
+impl Toolbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn connect(&mut self, mut client: Client) -> Result<(), Error> {
+        let tools = client
+            .list_tools()
+            .await?
+            .into_iter()
+            .map(Tool::from)
+            .collect();
+
+        self.servers.push(Server { client, tools });
+
+        Ok(())
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = &Client> {
+        self.servers.iter().map(|server| &server.client)
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.servers
+            .iter()
+            .flat_map(|server| server.tools.iter().cloned())
+            .collect()
+    }
+}
+
+impl tool::Handler for Toolbox {
+    async fn call(&mut self, call: &tool::Call) -> Result<String, Error> {
+        let tool::Call::Function {
+            name, arguments, ..
+        } = call;
+
+        let Some(server) = self.servers.iter_mut().find(|server| {
+            server
+                .tools
+                .iter()
+                .any(|Tool::Function { function }| &function.name == name)
+        }) else {
+            return Err(Error::ToolNotFound(name.clone()));
+        };
+
+        let arguments = if arguments.trim().is_empty() {
+            mcp::Value::Object(Default::default())
+        } else {
+            serde_json::from_str(arguments)?
+        };
+
+        let response = server.client.call_tool(name, arguments).await?;
+
+        content(response.content)
+    }
+}
+
+fn content(content: Content) -> Result<String, Error> {
+    Ok(match content {
+        Content::Unstructured(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Unstructured::Text { text } => Some(text),
+                Unstructured::Resource { text, .. } => Some(text),
+                Unstructured::Image { .. }
+                | Unstructured::Audio { .. }
+                | Unstructured::ResourceLink { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Content::Structured(value) => serde_json::to_string(&value)?,
+    })
+}
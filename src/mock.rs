@@ -0,0 +1,384 @@
+use crate::{Error, Url};
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task;
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+#[derive(Debug)]
+pub struct Server {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    handle: task::JoinHandle<()>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    completions: VecDeque<Completion>,
+    requests: Vec<Value>,
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl Server {
+    pub async fn start() -> Result<Self, Error> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let handle = task::spawn({
+            let state = state.clone();
+
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let state = state.clone();
+
+                    let _handle = task::spawn(async move {
+                        let _ = respond(stream, &state).await;
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            address,
+            state,
+            handle,
+        })
+    }
+
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{address}", address = self.address))
+            .expect("socket address must be a valid url")
+    }
+
+    pub fn respond(&self, completion: Completion) {
+        self.state().completions.push_back(completion);
+    }
+
+    pub fn serve(&self, path: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        let _ = self.state().files.insert(path.into(), contents.into());
+    }
+
+    pub fn requests(&self) -> Vec<Value> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    chunks: Vec<String>,
+    fragment_size: Option<usize>,
+    calls: usize,
+}
+
+impl Completion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.delta(json!({ "content": text.into() }))
+    }
+
+    pub fn think(self, reasoning: impl Into<String>) -> Self {
+        self.text("<think>").text(reasoning).text("</think>")
+    }
+
+    pub fn tool_call(
+        mut self,
+        id: impl Into<String>,
+        name: impl Into<String>,
+        arguments: impl Into<String>,
+    ) -> Self {
+        let index = self.calls;
+        self.calls += 1;
+
+        self.delta(json!({
+            "tool_calls": [{
+                "index": index,
+                "id": id.into(),
+                "type": "function",
+                "function": {
+                    "name": name.into(),
+                    "arguments": arguments.into(),
+                },
+            }],
+        }))
+    }
+
+    pub fn arguments(self, delta: impl Into<String>) -> Self {
+        let index = self.calls.saturating_sub(1);
+
+        self.arguments_at(index, delta)
+    }
+
+    pub fn arguments_at(self, index: usize, delta: impl Into<String>) -> Self {
+        self.delta(json!({
+            "tool_calls": [{
+                "index": index,
+                "function": {
+                    "arguments": delta.into(),
+                },
+            }],
+        }))
+    }
+
+    pub fn finish(self, reason: impl Into<String>) -> Self {
+        self.data(json!({
+            "choices": [{
+                "index": 0,
+                "delta": {},
+                "finish_reason": reason.into(),
+            }],
+        }))
+    }
+
+    pub fn usage(self, prompt_tokens: u64, completion_tokens: u64) -> Self {
+        self.data(json!({
+            "choices": [],
+            "usage": {
+                "prompt_tokens": prompt_tokens,
+                "completion_tokens": completion_tokens,
+                "total_tokens": prompt_tokens + completion_tokens,
+            },
+        }))
+    }
+
+    pub fn data(self, data: Value) -> Self {
+        self.chunk(format!("data: {data}\n\n"))
+    }
+
+    pub fn chunk(mut self, chunk: impl Into<String>) -> Self {
+        self.chunks.push(chunk.into());
+        self
+    }
+
+    pub fn fragmented(self, size: usize) -> Self {
+        Self {
+            fragment_size: Some(size.max(1)),
+            ..self
+        }
+    }
+
+    fn delta(self, delta: Value) -> Self {
+        self.data(json!({
+            "choices": [{
+                "index": 0,
+                "delta": delta,
+                "finish_reason": null,
+            }],
+        }))
+    }
+}
+
+async fn respond(stream: TcpStream, state: &Mutex<State>) -> Result<(), Error> {
+    stream.set_nodelay(true)?;
+
+    let mut stream = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    let _ = stream.read_line(&mut request_line).await?;
+
+    let mut content_length = 0;
+    let mut range_start = None;
+
+    loop {
+        let mut header = String::new();
+        let _ = stream.read_line(&mut header).await?;
+
+        let header = header.trim();
+
+        if header.is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().unwrap_or_default();
+        } else if name.eq_ignore_ascii_case("range") {
+            range_start = value
+                .trim()
+                .strip_prefix("bytes=")
+                .and_then(|range| range.strip_suffix('-'))
+                .and_then(|start| start.parse::<usize>().ok());
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await?;
+
+    let mut stream = stream.into_inner();
+
+    let path = request_line.split(' ').nth(1).unwrap_or_default();
+
+    let file = state
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .files
+        .get(path)
+        .cloned();
+
+    if let Some(file) = file {
+        return write_file(&mut stream, &file, range_start).await;
+    }
+
+    match path {
+        "/health" => write_json(&mut stream, "200 OK", json!({ "status": "ok" })).await,
+        "/v1/models" => {
+            write_json(
+                &mut stream,
+                "200 OK",
+                json!({
+                    "object": "list",
+                    "data": [{ "id": "mock", "object": "model" }],
+                }),
+            )
+            .await
+        }
+        "/v1/chat/completions" => {
+            let completion = {
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+
+                state
+                    .requests
+                    .push(serde_json::from_slice(&body).unwrap_or(Value::Null));
+
+                state.completions.pop_front()
+            };
+
+            let Some(completion) = completion else {
+                return write_json(
+                    &mut stream,
+                    "400 Bad Request",
+                    json!({ "error": { "message": "no completion was scripted" } }),
+                )
+                .await;
+            };
+
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\n\
+                    Content-Type: text/event-stream\r\n\
+                    Cache-Control: no-cache\r\n\
+                    Connection: close\r\n\r\n",
+                )
+                .await?;
+
+            let chunks = completion
+                .chunks
+                .iter()
+                .map(String::as_str)
+                .chain(["data: [DONE]\n\n"]);
+
+            for chunk in chunks {
+                match completion.fragment_size {
+                    Some(size) => {
+                        for fragment in chunk.as_bytes().chunks(size) {
+                            stream.write_all(fragment).await?;
+                            stream.flush().await?;
+                        }
+                    }
+                    None => {
+                        stream.write_all(chunk.as_bytes()).await?;
+                        stream.flush().await?;
+                    }
+                }
+            }
+
+            stream.shutdown().await?;
+
+            Ok(())
+        }
+        _ => {
+            write_json(
+                &mut stream,
+                "404 Not Found",
+                json!({ "error": { "message": "not found" } }),
+            )
+            .await
+        }
+    }
+}
+
+async fn write_json(stream: &mut TcpStream, status: &str, body: Value) -> Result<(), Error> {
+    let body = body.to_string();
+
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {status}\r\n\
+                Content-Type: application/json\r\n\
+                Content-Length: {length}\r\n\
+                Connection: close\r\n\r\n\
+                {body}",
+                length = body.len(),
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+async fn write_file(
+    stream: &mut TcpStream,
+    file: &[u8],
+    range_start: Option<usize>,
+) -> Result<(), Error> {
+    let length = file.len();
+
+    let head = match range_start {
+        Some(start) if start >= length => {
+            format!(
+                "HTTP/1.1 416 Range Not Satisfiable\r\n\
+                Content-Range: bytes */{length}\r\n\
+                Content-Length: 0\r\n\
+                Connection: close\r\n\r\n"
+            )
+        }
+        Some(start) => {
+            format!(
+                "HTTP/1.1 206 Partial Content\r\n\
+                Content-Range: bytes {start}-{end}/{length}\r\n\
+                Content-Length: {remaining}\r\n\
+                Connection: close\r\n\r\n",
+                end = length - 1,
+                remaining = length - start,
+            )
+        }
+        None => {
+            format!(
+                "HTTP/1.1 200 OK\r\n\
+                Content-Length: {length}\r\n\
+                Connection: close\r\n\r\n"
+            )
+        }
+    };
+
+    let start = range_start.unwrap_or_default().min(length);
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&file[start..]).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
@@ -0,0 +1,422 @@
+use crate::Error;
+
+use sha2::{Digest, Sha256};
+use sipper::{Straw, sipper};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    pub location: Location,
+    pub sha256: Option<String>,
+    pub projector: Option<Location>,
+}
+
+impl Model {
+    pub fn new(location: impl Into<Location>) -> Self {
+        Self {
+            location: location.into(),
+            sha256: None,
+            projector: None,
+        }
+    }
+
+    pub fn url(url: Url) -> Self {
+        Self::new(Location::Url(url))
+    }
+
+    pub fn hugging_face(repository: impl Into<String>, file: impl Into<String>) -> Self {
+        Self::new(Location::HuggingFace {
+            repository: repository.into(),
+            file: file.into(),
+            revision: "main".to_owned(),
+        })
+    }
+
+    pub fn sha256(self, checksum: impl Into<String>) -> Self {
+        Self {
+            sha256: Some(checksum.into()),
+            ..self
+        }
+    }
+
+    pub fn projector(self, projector: impl Into<Location>) -> Self {
+        Self {
+            projector: Some(projector.into()),
+            ..self
+        }
+    }
+}
+
+impl<T> From<T> for Model
+where
+    T: Into<Location>,
+{
+    fn from(location: T) -> Self {
+        Self::new(location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Path(PathBuf),
+    Url(Url),
+    HuggingFace {
+        repository: String,
+        file: String,
+        revision: String,
+    },
+}
+
+impl Location {
+    const HUGGING_FACE_PREFIX: &'static str = "hf://";
+
+    pub fn parse(location: &str) -> Self {
+        if let Some(reference) = location.strip_prefix(Self::HUGGING_FACE_PREFIX) {
+            let mut parts = reference.splitn(3, '/');
+
+            if let (Some(owner), Some(name), Some(file)) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                let (file, revision) = file.split_once('@').unwrap_or((file, "main"));
+
+                return Self::HuggingFace {
+                    repository: format!("{owner}/{name}"),
+                    file: file.to_owned(),
+                    revision: revision.to_owned(),
+                };
+            }
+        }
+
+        if (location.starts_with("http://") || location.starts_with("https://"))
+            && let Ok(url) = Url::parse(location)
+        {
+            return Self::Url(url);
+        }
+
+        Self::Path(PathBuf::from(location))
+    }
+
+    pub fn url(&self) -> Result<Option<Url>, Error> {
+        Ok(match self {
+            Self::Path(_) => None,
+            Self::Url(url) => Some(url.clone()),
+            Self::HuggingFace {
+                repository,
+                file,
+                revision,
+            } => Some(Url::parse(&format!(
+                "https://huggingface.co/{repository}/resolve/{revision}/{file}"
+            ))?),
+        })
+    }
+
+    pub(crate) fn fetch<'a>(
+        &'a self,
+        cache: &'a Path,
+        sha256: Option<&'a str>,
+    ) -> impl Straw<PathBuf, u32, Error> + 'a {
+        sipper(async move |mut progress| {
+            let Some(url) = self.url()? else {
+                let Self::Path(path) = self else {
+                    unreachable!("only local paths lack a URL");
+                };
+
+                return Ok(path.clone());
+            };
+
+            let path = cache
+                .join(url.host_str().unwrap_or("unknown"))
+                .join(url.path().trim_start_matches('/'));
+
+            if fs::try_exists(&path).await? {
+                return Ok(path);
+            }
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+
+            let partial = {
+                let mut partial = path.clone().into_os_string();
+                partial.push(".part");
+
+                PathBuf::from(partial)
+            };
+
+            let offset = fs::metadata(&partial)
+                .await
+                .map(|metadata| metadata.len())
+                .unwrap_or_default();
+
+            let client = reqwest::Client::new();
+            let mut request = client.get(url.clone());
+
+            if offset > 0 {
+                request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+            }
+
+            let mut response = request.send().await?;
+
+            if offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+                let length = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|range| range.to_str().ok())
+                    .and_then(|range| range.strip_prefix("bytes */"))
+                    .and_then(|length| length.parse::<u64>().ok());
+
+                // The partial file may already hold the whole model; otherwise start over
+                if length == Some(offset) {
+                    progress.send(100).await;
+
+                    return finish(&partial, &path, sha256).await;
+                }
+
+                fs::remove_file(&partial).await?;
+                response = client.get(url).send().await?;
+            }
+
+            let mut response = response.error_for_status()?;
+            let is_resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(is_resumed)
+                .truncate(!is_resumed)
+                .open(&partial)
+                .await?;
+
+            let mut downloaded = if is_resumed { offset } else { 0 };
+            let total = response.content_length().map(|length| length + downloaded);
+            let mut last_percent = None;
+
+            while let Some(chunk) = response.chunk().await? {
+                file.write_all(&chunk).await?;
+                downloaded += chunk.len() as u64;
+
+                if let Some(total) = total {
+                    let percent = (downloaded * 100 / total.max(1)) as u32;
+
+                    if last_percent != Some(percent) {
+                        last_percent = Some(percent);
+                        progress.send(percent).await;
+                    }
+                }
+            }
+
+            file.flush().await?;
+            drop(file);
+
+            finish(&partial, &path, sha256).await
+        })
+    }
+}
+
+impl From<&str> for Location {
+    fn from(location: &str) -> Self {
+        Self::parse(location)
+    }
+}
+
+impl From<String> for Location {
+    fn from(location: String) -> Self {
+        Self::parse(&location)
+    }
+}
+
+impl From<&String> for Location {
+    fn from(location: &String) -> Self {
+        Self::parse(location)
+    }
+}
+
+impl From<PathBuf> for Location {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&PathBuf> for Location {
+    fn from(path: &PathBuf) -> Self {
+        Self::Path(path.clone())
+    }
+}
+
+impl From<&Path> for Location {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_owned())
+    }
+}
+
+impl From<Url> for Location {
+    fn from(url: Url) -> Self {
+        Self::Url(url)
+    }
+}
+
+pub fn cache_dir() -> PathBuf {
+    if let Some(cache) = env::var_os("REASON_CACHE") {
+        return PathBuf::from(cache);
+    }
+
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+
+    base.join("reason").join("models")
+}
+
+async fn finish(partial: &Path, path: &Path, sha256: Option<&str>) -> Result<PathBuf, Error> {
+    if let Some(expected) = sha256 {
+        let found = checksum(partial).await?;
+
+        if !found.eq_ignore_ascii_case(expected) {
+            fs::remove_file(partial).await?;
+
+            return Err(Error::ChecksumMismatched {
+                expected: expected.to_owned(),
+                found,
+            });
+        }
+    }
+
+    fs::rename(partial, path).await?;
+
+    Ok(path.to_owned())
+}
+
+async fn checksum(path: &Path) -> Result<String, Error> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+
+    use crate::mock;
+
+    use sipper::Sipper;
+
+    const MODEL: &[u8] = &[42; 1 << 16];
+
+    async fn setup(name: &str) -> Result<(mock::Server, Location, PathBuf), Error> {
+        let server = mock::Server::start().await?;
+        server.serve("/model.gguf", MODEL);
+
+        let location = Location::Url(server.url().join("/model.gguf")?);
+        let cache = env::temp_dir().join(format!("reason-{name}-{}", std::process::id()));
+
+        let _ = fs::remove_dir_all(&cache).await;
+
+        Ok((server, location, cache))
+    }
+
+    fn partial_path(cache: &Path) -> PathBuf {
+        cache.join("127.0.0.1").join("model.gguf.part")
+    }
+
+    #[tokio::test]
+    async fn it_downloads_and_reports_progress() -> Result<(), Error> {
+        let (_server, location, cache) = setup("download").await?;
+
+        let mut fetch = location.fetch(&cache, None).pin();
+        let mut progress = Vec::new();
+
+        while let Some(percent) = fetch.sip().await {
+            progress.push(percent);
+        }
+
+        let path = fetch.await?;
+
+        assert_eq!(fs::read(&path).await?, MODEL);
+        assert_eq!(progress.last(), Some(&100));
+        assert!(progress.is_sorted());
+
+        fs::remove_dir_all(&cache).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_resumes_partial_downloads() -> Result<(), Error> {
+        let (_server, location, cache) = setup("resume").await?;
+
+        let partial = partial_path(&cache);
+        fs::create_dir_all(partial.parent().unwrap()).await?;
+
+        // The prefix differs from the served file, so it only survives a resumed download
+        fs::write(&partial, [7; 1024]).await?;
+
+        let path = location.fetch(&cache, None).await?;
+        let contents = fs::read(&path).await?;
+
+        assert_eq!(contents[..1024], [7; 1024]);
+        assert_eq!(contents[1024..], MODEL[1024..]);
+        assert!(!fs::try_exists(&partial).await?);
+
+        fs::remove_dir_all(&cache).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_completes_fully_downloaded_parts() -> Result<(), Error> {
+        let (_server, location, cache) = setup("complete").await?;
+
+        let partial = partial_path(&cache);
+        fs::create_dir_all(partial.parent().unwrap()).await?;
+        fs::write(&partial, MODEL).await?;
+
+        let sha256 = format!("{:x}", Sha256::digest(MODEL));
+        let path = location.fetch(&cache, Some(&sha256)).await?;
+
+        assert_eq!(fs::read(&path).await?, MODEL);
+
+        fs::remove_dir_all(&cache).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_rejects_checksum_mismatches() -> Result<(), Error> {
+        let (_server, location, cache) = setup("checksum").await?;
+
+        let result = location.fetch(&cache, Some("deadbeef")).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::ChecksumMismatched { expected, .. }) if expected == "deadbeef"
+        ));
+        assert!(!fs::try_exists(partial_path(&cache)).await?);
+
+        fs::remove_dir_all(&cache).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::Status;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    Logged(String),
+    StatusChanged(Status),
+    SlotsReported(Vec<Slot>),
+    MetricsReported(Metrics),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Slot {
+    pub id: usize,
+    #[serde(rename = "n_ctx")]
+    pub context_size: u32,
+    #[serde(default)]
+    pub is_processing: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub prompt_tokens: u64,
+    pub predicted_tokens: u64,
+    pub prompt_per_second: f64,
+    pub predicted_per_second: f64,
+    pub kv_cache_usage: f64,
+    pub kv_cache_tokens: u64,
+    pub requests_processing: u64,
+    pub requests_deferred: u64,
+}
+
+impl Metrics {
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut metrics = Self::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, value)) = line.split_once(' ') else {
+                continue;
+            };
+
+            let Ok(value) = value.trim().parse::<f64>() else {
+                continue;
+            };
+
+            let count = value.max(0.0) as u64;
+
+            match name.trim_start_matches("llamacpp:") {
+                "prompt_tokens_total" => metrics.prompt_tokens = count,
+                "tokens_predicted_total" => metrics.predicted_tokens = count,
+                "prompt_tokens_seconds" => metrics.prompt_per_second = value,
+                "predicted_tokens_seconds" => metrics.predicted_per_second = value,
+                "kv_cache_usage_ratio" => metrics.kv_cache_usage = value,
+                "kv_cache_tokens" => metrics.kv_cache_tokens = count,
+                "requests_processing" => metrics.requests_processing = count,
+                "requests_deferred" => metrics.requests_deferred = count,
+                _ => {}
+            }
+        }
+
+        metrics
+    }
+}
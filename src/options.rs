@@ -0,0 +1,153 @@
+use crate::Interrupt;
+use crate::tool::Schema;
+
+use serde::{Serialize, Serializer};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Options {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grammar: Option<String>,
+    #[serde(
+        rename = "response_format",
+        serialize_with = "serialize_schema",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schema: Option<Schema>,
+    #[serde(skip)]
+    pub interrupt: Option<Interrupt>,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn temperature(self, temperature: f32) -> Self {
+        Self {
+            temperature: Some(temperature),
+            ..self
+        }
+    }
+
+    pub fn top_p(self, top_p: f32) -> Self {
+        Self {
+            top_p: Some(top_p),
+            ..self
+        }
+    }
+
+    pub fn top_k(self, top_k: u32) -> Self {
+        Self {
+            top_k: Some(top_k),
+            ..self
+        }
+    }
+
+    pub fn min_p(self, min_p: f32) -> Self {
+        Self {
+            min_p: Some(min_p),
+            ..self
+        }
+    }
+
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    pub fn max_tokens(self, max_tokens: u32) -> Self {
+        Self {
+            max_tokens: Some(max_tokens),
+            ..self
+        }
+    }
+
+    pub fn stop(mut self, sequence: impl Into<String>) -> Self {
+        self.stop.push(sequence.into());
+        self
+    }
+
+    pub fn repeat_penalty(self, repeat_penalty: f32) -> Self {
+        Self {
+            repeat_penalty: Some(repeat_penalty),
+            ..self
+        }
+    }
+
+    pub fn grammar(self, grammar: impl Into<String>) -> Self {
+        Self {
+            grammar: Some(grammar.into()),
+            ..self
+        }
+    }
+
+    pub fn schema(self, schema: Schema) -> Self {
+        Self {
+            schema: Some(schema),
+            ..self
+        }
+    }
+
+    pub fn interrupt(self, interrupt: &Interrupt) -> Self {
+        Self {
+            interrupt: Some(interrupt.clone()),
+            ..self
+        }
+    }
+
+    pub fn or(&self, defaults: &Self) -> Self {
+        Self {
+            temperature: self.temperature.or(defaults.temperature),
+            top_p: self.top_p.or(defaults.top_p),
+            top_k: self.top_k.or(defaults.top_k),
+            min_p: self.min_p.or(defaults.min_p),
+            seed: self.seed.or(defaults.seed),
+            max_tokens: self.max_tokens.or(defaults.max_tokens),
+            stop: if self.stop.is_empty() {
+                defaults.stop.clone()
+            } else {
+                self.stop.clone()
+            },
+            repeat_penalty: self.repeat_penalty.or(defaults.repeat_penalty),
+            grammar: self.grammar.clone().or_else(|| defaults.grammar.clone()),
+            schema: self.schema.clone().or_else(|| defaults.schema.clone()),
+            interrupt: self
+                .interrupt
+                .clone()
+                .or_else(|| defaults.interrupt.clone()),
+        }
+    }
+}
+
+fn serialize_schema<S: Serializer>(
+    schema: &Option<Schema>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": "response",
+            "schema": schema,
+        },
+    })
+    .serialize(serializer)
+}
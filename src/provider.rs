@@ -0,0 +1,430 @@
+use crate::tool::{self, Tool};
+use crate::{Error, Event, FinishReason, Message, Options, Output, Reasoning, Timings, Usage};
+
+use reqwest::header::{self, HeaderName};
+use serde::Deserialize;
+use serde_json::json;
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+mod anthropic;
+mod ollama;
+
+pub use anthropic::Anthropic;
+pub use ollama::Ollama;
+
+pub trait Provider: fmt::Debug + Send + Sync {
+    fn models(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        client.get(format!("{host}/v1/models"))
+    }
+
+    fn health(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        client.get(format!("{host}/health"))
+    }
+
+    fn authorization(&self, api_key: &str) -> (HeaderName, String) {
+        (header::AUTHORIZATION, format!("Bearer {api_key}"))
+    }
+
+    fn chat(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        request: Request<'_>,
+    ) -> Result<reqwest::RequestBuilder, Error>;
+
+    fn decoder(&self) -> Box<dyn Decoder>;
+}
+
+pub trait Decoder: Send {
+    fn decode(&mut self, line: &[u8]) -> Result<Vec<Event>, Error>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Request<'a> {
+    pub model: &'a str,
+    pub messages: &'a [Message],
+    pub append: &'a [Message],
+    pub tools: &'a [Tool],
+    pub options: &'a Options,
+}
+
+impl<'a> Request<'a> {
+    pub fn messages(&self) -> impl Iterator<Item = &'a Message> {
+        self.messages.iter().chain(self.append)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenAiCompatible {
+    #[default]
+    LlamaCpp,
+    OpenAi,
+    Groq,
+    Vllm,
+    LmStudio,
+}
+
+impl OpenAiCompatible {
+    fn adapt(self, options: &mut serde_json::Map<String, serde_json::Value>) {
+        let mut rename = |from: &str, to: &str| {
+            if let Some(value) = options.remove(from) {
+                let _ = options.insert(to.to_owned(), value);
+            }
+        };
+
+        match self {
+            Self::LlamaCpp => {}
+            Self::OpenAi => {
+                rename("max_tokens", "max_completion_tokens");
+
+                for unsupported in ["top_k", "min_p", "repeat_penalty", "grammar"] {
+                    let _ = options.remove(unsupported);
+                }
+            }
+            Self::Groq => {
+                for unsupported in ["top_k", "min_p", "repeat_penalty", "grammar"] {
+                    let _ = options.remove(unsupported);
+                }
+            }
+            Self::Vllm => {
+                rename("repeat_penalty", "repetition_penalty");
+                rename("grammar", "guided_grammar");
+            }
+            Self::LmStudio => {
+                let _ = options.remove("grammar");
+            }
+        }
+    }
+}
+
+impl Provider for OpenAiCompatible {
+    fn chat(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        request: Request<'_>,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let messages: Vec<_> = request.messages().map(Message::to_json).collect();
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "tools": request.tools,
+            "stream": true,
+            "stream_options": {
+                "include_usage": true,
+            },
+        });
+
+        let object = body.as_object_mut().expect("body must be an object");
+
+        if *self == Self::LlamaCpp {
+            let _ = object.insert("cache_prompt".to_owned(), json!(true));
+        } else if request.tools.is_empty() {
+            // Hosted APIs reject an empty list of tools
+            let _ = object.remove("tools");
+        }
+
+        if let serde_json::Value::Object(mut options) = serde_json::to_value(request.options)? {
+            self.adapt(&mut options);
+            object.extend(options);
+        }
+
+        Ok(client
+            .post(format!("{host}/v1/chat/completions"))
+            .json(&body))
+    }
+
+    fn decoder(&self) -> Box<dyn Decoder> {
+        Box::new(Completions::default())
+    }
+}
+
+#[derive(Debug)]
+struct Completions {
+    mode: Option<Mode>,
+    mode_started_at: Instant,
+    usage: Usage,
+    calls: usize,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Reasoning,
+    Messaging,
+    ToolCalling,
+}
+
+impl Default for Completions {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            mode_started_at: Instant::now(),
+            usage: Usage::default(),
+            calls: 0,
+        }
+    }
+}
+
+impl Decoder for Completions {
+    fn decode(&mut self, line: &[u8]) -> Result<Vec<Event>, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            #[serde(default)]
+            choices: Vec<Choice>,
+            usage: Option<TokenUsage>,
+            timings: Option<RawTimings>,
+            error: Option<ApiError>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ApiError {
+            Object { message: String },
+            Message(String),
+        }
+
+        #[derive(Deserialize)]
+        struct Choice {
+            #[serde(default)]
+            delta: Delta,
+            finish_reason: Option<String>,
+            #[serde(alias = "stopping_word")]
+            stop_reason: Option<serde_json::Value>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Delta {
+            Text { content: String },
+            Call { tool_calls: Vec<ToolCall> },
+            Empty {},
+        }
+
+        impl Default for Delta {
+            fn default() -> Self {
+                Self::Empty {}
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct TokenUsage {
+            prompt_tokens: u64,
+            completion_tokens: u64,
+            prompt_tokens_details: Option<TokenDetails>,
+        }
+
+        #[derive(Deserialize)]
+        struct TokenDetails {
+            #[serde(default)]
+            cached_tokens: u64,
+        }
+
+        #[derive(Deserialize)]
+        struct RawTimings {
+            prompt_n: u64,
+            prompt_ms: f64,
+            prompt_per_second: f64,
+            predicted_n: u64,
+            predicted_ms: f64,
+            predicted_per_second: f64,
+            cache_n: Option<u64>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ToolCall {
+            New {
+                index: Option<usize>,
+                id: tool::Id,
+                function: Function,
+            },
+            Update {
+                index: Option<usize>,
+                function: FunctionUpdate,
+            },
+        }
+
+        #[derive(Deserialize)]
+        struct Function {
+            name: String,
+            #[serde(default)]
+            arguments: String,
+        }
+
+        #[derive(Deserialize)]
+        struct FunctionUpdate {
+            arguments: String,
+        }
+
+        const PREFIX: usize = b"data:".len();
+
+        let failed = |error: ApiError| {
+            Error::CompletionFailed(match error {
+                ApiError::Object { message } | ApiError::Message(message) => message,
+            })
+        };
+
+        let mut events = Vec::new();
+
+        // llama-server may report failures mid-stream as `error:` lines
+        if let Some(error) = line.strip_prefix(b"error:") {
+            return Err(match serde_json::from_slice(error) {
+                Ok(error) => failed(error),
+                Err(_) => Error::CompletionFailed(String::from_utf8_lossy(error).trim().to_owned()),
+            });
+        }
+
+        if line.len() < PREFIX {
+            return Ok(events);
+        }
+
+        let Ok(data): Result<Data, _> = serde_json::from_slice(&line[PREFIX..]) else {
+            return Ok(events);
+        };
+
+        if let Some(error) = data.error {
+            return Err(failed(error));
+        }
+
+        if data.usage.is_some() || data.timings.is_some() {
+            if let Some(tokens) = data.usage {
+                self.usage.prompt_tokens = tokens.prompt_tokens;
+                self.usage.completion_tokens = tokens.completion_tokens;
+
+                if let Some(details) = tokens.prompt_tokens_details {
+                    self.usage.cached_tokens = details.cached_tokens;
+                }
+            }
+
+            if let Some(timings) = data.timings {
+                if let Some(cached_tokens) = timings.cache_n {
+                    self.usage.cached_tokens = cached_tokens;
+                }
+
+                self.usage.timings = Some(Timings {
+                    prompt_tokens: timings.prompt_n,
+                    prompt_duration: Duration::from_secs_f64(timings.prompt_ms.max(0.0) / 1000.0),
+                    prompt_per_second: timings.prompt_per_second,
+                    predicted_tokens: timings.predicted_n,
+                    predicted_duration: Duration::from_secs_f64(
+                        timings.predicted_ms.max(0.0) / 1000.0,
+                    ),
+                    predicted_per_second: timings.predicted_per_second,
+                });
+            }
+
+            events.push(Event::UsageReported {
+                usage: self.usage.clone(),
+            });
+        }
+
+        let Some(choice) = data.choices.into_iter().next() else {
+            return Ok(events);
+        };
+
+        match choice.delta {
+            Delta::Text { content } => {
+                match self.mode {
+                    None | Some(Mode::Messaging) if content.contains("<think>") => {
+                        self.mode = Some(Mode::Reasoning);
+                        self.mode_started_at = Instant::now();
+
+                        events.push(Event::OutputAdded {
+                            output: Output::Reasoning(Reasoning::default()),
+                        });
+
+                        return Ok(events);
+                    }
+                    Some(Mode::Reasoning) if content.contains("</think>") => {
+                        self.mode = Some(Mode::Messaging);
+                        self.mode_started_at = Instant::now();
+
+                        events.push(Event::OutputAdded {
+                            output: Output::Message(String::new()),
+                        });
+
+                        return Ok(events);
+                    }
+                    None => {
+                        self.mode = Some(Mode::Messaging);
+                        self.mode_started_at = Instant::now();
+
+                        events.push(Event::OutputAdded {
+                            output: Output::Message(String::new()),
+                        });
+                    }
+                    _ => {}
+                }
+
+                if let Some(Mode::Reasoning | Mode::Messaging) = self.mode {
+                    events.push(Event::TextChanged {
+                        delta: content,
+                        duration: self.mode_started_at.elapsed(),
+                    });
+                }
+            }
+            Delta::Call { tool_calls } => {
+                if !matches!(self.mode, Some(Mode::ToolCalling)) {
+                    self.mode = Some(Mode::ToolCalling);
+                    self.mode_started_at = Instant::now();
+
+                    events.push(Event::OutputAdded {
+                        output: Output::ToolCalls(Vec::new()),
+                    });
+
+                    self.calls = 0;
+                }
+
+                for tool_call in tool_calls {
+                    match tool_call {
+                        ToolCall::New {
+                            index,
+                            id,
+                            function,
+                        } => {
+                            let index = index.unwrap_or(self.calls);
+                            self.calls = self.calls.max(index + 1);
+
+                            events.push(Event::ToolCallAdded {
+                                index,
+                                id,
+                                name: function.name,
+                                arguments: function.arguments,
+                            });
+                        }
+                        ToolCall::Update { index, function } => {
+                            events.push(Event::ArgumentsChanged {
+                                index: index.unwrap_or(self.calls.saturating_sub(1)),
+                                delta: function.arguments,
+                                duration: self.mode_started_at.elapsed(),
+                            });
+                        }
+                    }
+                }
+            }
+            Delta::Empty {} => {}
+        }
+
+        if let Some(finish_reason) = choice.finish_reason {
+            let reason = match finish_reason.as_str() {
+                "stop" => match choice.stop_reason {
+                    Some(serde_json::Value::String(sequence)) if !sequence.is_empty() => {
+                        FinishReason::StopSequence(sequence)
+                    }
+                    _ => FinishReason::Stop,
+                },
+                "length" => FinishReason::Length,
+                "tool_calls" => FinishReason::ToolCalls,
+                "content_filter" => FinishReason::ContentFilter,
+                _ => FinishReason::Other(finish_reason),
+            };
+
+            events.push(Event::Finished { reason });
+        }
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,514 @@
+use crate::provider::{Decoder, Provider, Request};
+use crate::tool::{self, Tool};
+use crate::{Error, Event, FinishReason, Image, Message, Output, Part, Reasoning, Usage};
+
+use base64::Engine;
+use reqwest::header::HeaderName;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use std::time::Instant;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anthropic {
+    pub api_key: Option<String>,
+    pub version: String,
+    pub max_tokens: u32,
+    pub thinking: Option<u32>,
+}
+
+impl Anthropic {
+    pub const VERSION: &'static str = "2023-06-01";
+    pub const MAX_TOKENS: u32 = 4096;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn api_key(self, api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: Some(api_key.into()),
+            ..self
+        }
+    }
+
+    pub fn version(self, version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            ..self
+        }
+    }
+
+    pub fn max_tokens(self, max_tokens: u32) -> Self {
+        Self { max_tokens, ..self }
+    }
+
+    pub fn thinking(self, budget_tokens: u32) -> Self {
+        Self {
+            thinking: Some(budget_tokens),
+            ..self
+        }
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = request.header("anthropic-version", &self.version);
+
+        match &self.api_key {
+            Some(api_key) => request.header("x-api-key", api_key),
+            None => request,
+        }
+    }
+}
+
+impl Default for Anthropic {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            version: Self::VERSION.to_owned(),
+            max_tokens: Self::MAX_TOKENS,
+            thinking: None,
+        }
+    }
+}
+
+impl Provider for Anthropic {
+    fn models(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        self.authorize(client.get(format!("{host}/v1/models")))
+    }
+
+    fn health(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        self.models(client, host)
+    }
+
+    fn authorization(&self, api_key: &str) -> (HeaderName, String) {
+        (HeaderName::from_static("x-api-key"), api_key.to_owned())
+    }
+
+    fn chat(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        request: Request<'_>,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let options = request.options;
+
+        let unsupported = [
+            (options.schema.is_some(), "response schemas"),
+            (options.grammar.is_some(), "grammars"),
+            (options.seed.is_some(), "seeds"),
+            (options.min_p.is_some(), "min-p sampling"),
+            (options.repeat_penalty.is_some(), "repeat penalties"),
+        ];
+
+        if let Some((_, feature)) = unsupported.into_iter().find(|(is_set, _)| *is_set) {
+            return Err(Error::Unsupported {
+                provider: "Anthropic",
+                feature,
+            });
+        }
+
+        let mut system = Vec::new();
+        let mut messages: Vec<(&str, Vec<Value>)> = Vec::new();
+
+        for message in request.messages() {
+            let (role, content) = match message {
+                Message::System(content) => {
+                    system.push(content.as_str());
+                    continue;
+                }
+                Message::User(content) => ("user", vec![text(content)]),
+                Message::Multimodal(parts) => (
+                    "user",
+                    parts
+                        .iter()
+                        .map(|part| match part {
+                            Part::Text(content) => text(content),
+                            Part::Image(image) => self::image(image),
+                        })
+                        .collect(),
+                ),
+                Message::Assistant(Output::Reasoning(reasoning)) => {
+                    // Thinking can only be sent back together with its signature
+                    let Some(signature) = &reasoning.signature else {
+                        continue;
+                    };
+
+                    (
+                        "assistant",
+                        vec![json!({
+                            "type": "thinking",
+                            "thinking": reasoning.text,
+                            "signature": signature,
+                        })],
+                    )
+                }
+                Message::Assistant(Output::Message(content)) if content.is_empty() => continue,
+                Message::Assistant(Output::Message(content)) => ("assistant", vec![text(content)]),
+                Message::Assistant(Output::ToolCalls(calls)) => (
+                    "assistant",
+                    calls
+                        .iter()
+                        .map(|call| match call {
+                            tool::Call::Function {
+                                id,
+                                name,
+                                arguments,
+                            } => json!({
+                                "type": "tool_use",
+                                "id": id,
+                                "name": name,
+                                "input": serde_json::from_str(arguments)
+                                    .unwrap_or_else(|_| json!({})),
+                            }),
+                        })
+                        .collect(),
+                ),
+                Message::Tool(response) => (
+                    "user",
+                    vec![json!({
+                        "type": "tool_result",
+                        "tool_use_id": response.id,
+                        "content": response.content,
+                    })],
+                ),
+            };
+
+            match messages.last_mut() {
+                Some((last, blocks)) if *last == role => blocks.extend(content),
+                _ => messages.push((role, content)),
+            }
+        }
+
+        let messages: Vec<_> = messages
+            .into_iter()
+            .map(|(role, content)| {
+                json!({
+                    "role": role,
+                    "content": content,
+                })
+            })
+            .collect();
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": options.max_tokens.unwrap_or(self.max_tokens),
+            "stream": true,
+        });
+
+        let object = body.as_object_mut().expect("body must be an object");
+
+        let mut insert = |key: &str, value: Value| {
+            let _ = object.insert(key.to_owned(), value);
+        };
+
+        if !system.is_empty() {
+            insert("system", json!(system.join("\n\n")));
+        }
+
+        if !request.tools.is_empty() {
+            let tools: Vec<_> = request
+                .tools
+                .iter()
+                .map(|tool| match tool {
+                    Tool::Function { function } => json!({
+                        "name": function.name,
+                        "description": function.description,
+                        "input_schema": function.parameters,
+                    }),
+                })
+                .collect();
+
+            insert("tools", json!(tools));
+        }
+
+        if let Some(temperature) = options.temperature {
+            insert("temperature", json!(temperature));
+        }
+
+        if let Some(top_p) = options.top_p {
+            insert("top_p", json!(top_p));
+        }
+
+        if let Some(top_k) = options.top_k {
+            insert("top_k", json!(top_k));
+        }
+
+        if !options.stop.is_empty() {
+            insert("stop_sequences", json!(options.stop));
+        }
+
+        if let Some(budget_tokens) = self.thinking {
+            insert(
+                "thinking",
+                json!({
+                    "type": "enabled",
+                    "budget_tokens": budget_tokens,
+                }),
+            );
+        }
+
+        Ok(self.authorize(client.post(format!("{host}/v1/messages")).json(&body)))
+    }
+
+    fn decoder(&self) -> Box<dyn Decoder> {
+        Box::new(Messages::default())
+    }
+}
+
+fn text(content: &str) -> Value {
+    json!({
+        "type": "text",
+        "text": content,
+    })
+}
+
+fn image(image: &Image) -> Value {
+    match image {
+        Image::Data { mime, bytes } => json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": mime,
+                "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+            },
+        }),
+        Image::Url(url) => json!({
+            "type": "image",
+            "source": {
+                "type": "url",
+                "url": url.as_str(),
+            },
+        }),
+    }
+}
+
+#[derive(Debug)]
+struct Messages {
+    block: Option<Block>,
+    block_started_at: Instant,
+    usage: Usage,
+    calls: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Text,
+    Thinking,
+    ToolUse,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self {
+            block: None,
+            block_started_at: Instant::now(),
+            usage: Usage::default(),
+            calls: 0,
+        }
+    }
+}
+
+impl Decoder for Messages {
+    fn decode(&mut self, line: &[u8]) -> Result<Vec<Event>, Error> {
+        #[derive(Deserialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Data {
+            MessageStart {
+                message: Message,
+            },
+            ContentBlockStart {
+                content_block: ContentBlock,
+            },
+            ContentBlockDelta {
+                delta: Delta,
+            },
+            MessageDelta {
+                delta: MessageDelta,
+                usage: Option<TokenUsage>,
+            },
+            Error {
+                error: ApiError,
+            },
+            #[serde(other)]
+            Other,
+        }
+
+        #[derive(Deserialize)]
+        struct Message {
+            usage: Option<TokenUsage>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum ContentBlock {
+            Text,
+            Thinking,
+            ToolUse {
+                id: tool::Id,
+                name: String,
+            },
+            #[serde(other)]
+            Other,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(tag = "type")]
+        enum Delta {
+            #[serde(rename = "text_delta")]
+            Text { text: String },
+            #[serde(rename = "thinking_delta")]
+            Thinking { thinking: String },
+            #[serde(rename = "input_json_delta")]
+            InputJson { partial_json: String },
+            #[serde(rename = "signature_delta")]
+            Signature { signature: String },
+            #[serde(other)]
+            Other,
+        }
+
+        #[derive(Deserialize)]
+        struct MessageDelta {
+            stop_reason: Option<String>,
+            stop_sequence: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct TokenUsage {
+            input_tokens: Option<u64>,
+            output_tokens: Option<u64>,
+            cache_creation_input_tokens: Option<u64>,
+            cache_read_input_tokens: Option<u64>,
+        }
+
+        #[derive(Deserialize)]
+        struct ApiError {
+            #[serde(rename = "type")]
+            kind: String,
+            message: String,
+        }
+
+        let mut events = Vec::new();
+
+        let Some(data) = line.strip_prefix(b"data:") else {
+            return Ok(events);
+        };
+
+        let Ok(data): Result<Data, _> = serde_json::from_slice(data) else {
+            return Ok(events);
+        };
+
+        let record = |usage: &mut Usage, tokens: TokenUsage| {
+            let cache_created = tokens.cache_creation_input_tokens.unwrap_or_default();
+            let cache_read = tokens.cache_read_input_tokens.unwrap_or_default();
+
+            if let Some(input_tokens) = tokens.input_tokens {
+                usage.prompt_tokens = input_tokens + cache_created + cache_read;
+                usage.cached_tokens = cache_read;
+            }
+
+            if let Some(output_tokens) = tokens.output_tokens {
+                usage.completion_tokens = output_tokens;
+            }
+        };
+
+        match data {
+            Data::MessageStart { message } => {
+                if let Some(tokens) = message.usage {
+                    record(&mut self.usage, tokens);
+                }
+            }
+            Data::ContentBlockStart { content_block } => {
+                let block = match content_block {
+                    ContentBlock::Text => Block::Text,
+                    ContentBlock::Thinking => Block::Thinking,
+                    ContentBlock::ToolUse { .. } => Block::ToolUse,
+                    ContentBlock::Other => return Ok(events),
+                };
+
+                if block != Block::ToolUse || self.block != Some(Block::ToolUse) {
+                    self.block_started_at = Instant::now();
+
+                    events.push(Event::OutputAdded {
+                        output: match block {
+                            Block::Text => Output::Message(String::new()),
+                            Block::Thinking => Output::Reasoning(Reasoning::default()),
+                            Block::ToolUse => {
+                                self.calls = 0;
+
+                                Output::ToolCalls(Vec::new())
+                            }
+                        },
+                    });
+                }
+
+                self.block = Some(block);
+
+                if let ContentBlock::ToolUse { id, name } = content_block {
+                    events.push(Event::ToolCallAdded {
+                        index: self.calls,
+                        id,
+                        name,
+                        arguments: String::new(),
+                    });
+
+                    self.calls += 1;
+                }
+            }
+            Data::ContentBlockDelta { delta } => match delta {
+                Delta::Text { text: delta } | Delta::Thinking { thinking: delta } => {
+                    events.push(Event::TextChanged {
+                        delta,
+                        duration: self.block_started_at.elapsed(),
+                    });
+                }
+                Delta::InputJson { partial_json } => {
+                    events.push(Event::ArgumentsChanged {
+                        index: self.calls.saturating_sub(1),
+                        delta: partial_json,
+                        duration: self.block_started_at.elapsed(),
+                    });
+                }
+                Delta::Signature { signature } => {
+                    events.push(Event::ReasoningSigned { signature });
+                }
+                Delta::Other => {}
+            },
+            Data::MessageDelta { delta, usage } => {
+                if let Some(tokens) = usage {
+                    record(&mut self.usage, tokens);
+
+                    events.push(Event::UsageReported {
+                        usage: self.usage.clone(),
+                    });
+                }
+
+                if let Some(stop_reason) = delta.stop_reason {
+                    let reason = match stop_reason.as_str() {
+                        "end_turn" => FinishReason::Stop,
+                        "stop_sequence" => match delta.stop_sequence {
+                            Some(sequence) => FinishReason::StopSequence(sequence),
+                            None => FinishReason::Stop,
+                        },
+                        "max_tokens" => FinishReason::Length,
+                        "tool_use" => FinishReason::ToolCalls,
+                        "refusal" => FinishReason::ContentFilter,
+                        _ => FinishReason::Other(stop_reason),
+                    };
+
+                    events.push(Event::Finished { reason });
+                }
+            }
+            Data::Error { error } => {
+                return Err(Error::CompletionFailed(format!(
+                    "{kind}: {message}",
+                    kind = error.kind,
+                    message = error.message
+                )));
+            }
+            Data::Other => {}
+        }
+
+        Ok(events)
+    }
+}
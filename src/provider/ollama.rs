@@ -0,0 +1,385 @@
+use crate::provider::{Decoder, Provider, Request};
+use crate::tool;
+use crate::{Error, Event, FinishReason, Image, Message, Output, Part, Reasoning, Timings, Usage};
+
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ollama {
+    pub think: Option<bool>,
+    pub keep_alive: Option<String>,
+}
+
+impl Ollama {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn think(self, think: bool) -> Self {
+        Self {
+            think: Some(think),
+            ..self
+        }
+    }
+
+    pub fn keep_alive(self, keep_alive: impl Into<String>) -> Self {
+        Self {
+            keep_alive: Some(keep_alive.into()),
+            ..self
+        }
+    }
+}
+
+impl Provider for Ollama {
+    fn models(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        client.get(format!("{host}/api/tags"))
+    }
+
+    fn health(&self, client: &reqwest::Client, host: &str) -> reqwest::RequestBuilder {
+        client.get(format!("{host}/api/version"))
+    }
+
+    fn chat(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        request: Request<'_>,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let history: Vec<_> = request.messages().collect();
+
+        let messages: Vec<_> = history
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                Ok(match message {
+                    Message::System(content) => json!({
+                        "role": "system",
+                        "content": content,
+                    }),
+                    Message::User(content) => json!({
+                        "role": "user",
+                        "content": content,
+                    }),
+                    Message::Multimodal(parts) => {
+                        let mut content = Vec::new();
+                        let mut images = Vec::new();
+
+                        for part in parts {
+                            match part {
+                                Part::Text(text) => content.push(text.as_str()),
+                                Part::Image(Image::Data { bytes, .. }) => {
+                                    images.push(
+                                        base64::engine::general_purpose::STANDARD.encode(bytes),
+                                    );
+                                }
+                                Part::Image(Image::Url(_)) => {
+                                    return Err(Error::Unsupported {
+                                        provider: "Ollama",
+                                        feature: "image URLs",
+                                    });
+                                }
+                            }
+                        }
+
+                        json!({
+                            "role": "user",
+                            "content": content.join("\n"),
+                            "images": images,
+                        })
+                    }
+                    Message::Assistant(Output::Reasoning(reasoning)) => json!({
+                        "role": "assistant",
+                        "content": "",
+                        "thinking": reasoning.text,
+                    }),
+                    Message::Assistant(Output::Message(content)) => json!({
+                        "role": "assistant",
+                        "content": content,
+                    }),
+                    Message::Assistant(Output::ToolCalls(calls)) => {
+                        let tool_calls: Vec<_> = calls
+                            .iter()
+                            .map(|call| match call {
+                                tool::Call::Function {
+                                    name, arguments, ..
+                                } => json!({
+                                    "function": {
+                                        "name": name,
+                                        "arguments": serde_json::from_str::<Value>(arguments)
+                                            .unwrap_or_else(|_| json!({})),
+                                    },
+                                }),
+                            })
+                            .collect();
+
+                        json!({
+                            "role": "assistant",
+                            "content": "",
+                            "tool_calls": tool_calls,
+                        })
+                    }
+                    Message::Tool(response) => {
+                        // Ollama matches tool results by name instead of by id. Its ids are
+                        // made up per reply and repeat across turns, so the closest call wins
+                        let name = history[..i].iter().rev().find_map(|message| match message {
+                            Message::Assistant(Output::ToolCalls(calls)) => calls
+                                .iter()
+                                .find(|call| call.id() == &response.id)
+                                .map(|call| match call {
+                                    tool::Call::Function { name, .. } => name,
+                                }),
+                            _ => None,
+                        });
+
+                        let mut message = json!({
+                            "role": "tool",
+                            "content": response.content,
+                        });
+
+                        if let Some(name) = name {
+                            message["tool_name"] = json!(name);
+                        }
+
+                        message
+                    }
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let options = request.options;
+
+        let mut parameters = serde_json::Map::new();
+
+        let mut insert = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                let _ = parameters.insert(key.to_owned(), value);
+            }
+        };
+
+        insert("temperature", options.temperature.map(|n| json!(n)));
+        insert("top_p", options.top_p.map(|n| json!(n)));
+        insert("top_k", options.top_k.map(|n| json!(n)));
+        insert("min_p", options.min_p.map(|n| json!(n)));
+        insert("seed", options.seed.map(|n| json!(n)));
+        insert("num_predict", options.max_tokens.map(|n| json!(n)));
+        insert("repeat_penalty", options.repeat_penalty.map(|n| json!(n)));
+        insert(
+            "stop",
+            (!options.stop.is_empty()).then(|| json!(options.stop)),
+        );
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": true,
+            "options": parameters,
+        });
+
+        let object = body.as_object_mut().expect("body must be an object");
+
+        if !request.tools.is_empty() {
+            let _ = object.insert("tools".to_owned(), serde_json::to_value(request.tools)?);
+        }
+
+        if let Some(schema) = &options.schema {
+            let _ = object.insert("format".to_owned(), serde_json::to_value(schema)?);
+        }
+
+        if let Some(think) = self.think {
+            let _ = object.insert("think".to_owned(), json!(think));
+        }
+
+        if let Some(keep_alive) = &self.keep_alive {
+            let _ = object.insert("keep_alive".to_owned(), json!(keep_alive));
+        }
+
+        Ok(client.post(format!("{host}/api/chat")).json(&body))
+    }
+
+    fn decoder(&self) -> Box<dyn Decoder> {
+        Box::new(Chat::default())
+    }
+}
+
+#[derive(Debug)]
+struct Chat {
+    mode: Option<Mode>,
+    mode_started_at: Instant,
+    calls: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Reasoning,
+    Messaging,
+    ToolCalling,
+}
+
+impl Chat {
+    fn switch(&mut self, mode: Mode, events: &mut Vec<Event>) {
+        if self.mode == Some(mode) {
+            return;
+        }
+
+        self.mode = Some(mode);
+        self.mode_started_at = Instant::now();
+
+        events.push(Event::OutputAdded {
+            output: match mode {
+                Mode::Reasoning => Output::Reasoning(Reasoning::default()),
+                Mode::Messaging => Output::Message(String::new()),
+                Mode::ToolCalling => {
+                    self.calls = 0;
+
+                    Output::ToolCalls(Vec::new())
+                }
+            },
+        });
+    }
+}
+
+impl Default for Chat {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            mode_started_at: Instant::now(),
+            calls: 0,
+        }
+    }
+}
+
+impl Decoder for Chat {
+    fn decode(&mut self, line: &[u8]) -> Result<Vec<Event>, Error> {
+        #[derive(Deserialize)]
+        struct Data {
+            message: Option<Message>,
+            #[serde(default)]
+            done: bool,
+            done_reason: Option<String>,
+            prompt_eval_count: Option<u64>,
+            prompt_eval_duration: Option<u64>,
+            eval_count: Option<u64>,
+            eval_duration: Option<u64>,
+            error: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct Message {
+            #[serde(default)]
+            content: String,
+            #[serde(default)]
+            thinking: String,
+            #[serde(default)]
+            tool_calls: Vec<ToolCall>,
+        }
+
+        #[derive(Deserialize)]
+        struct ToolCall {
+            id: Option<tool::Id>,
+            function: Function,
+        }
+
+        #[derive(Deserialize)]
+        struct Function {
+            name: String,
+            #[serde(default)]
+            arguments: Value,
+        }
+
+        let mut events = Vec::new();
+
+        let Ok(data): Result<Data, _> = serde_json::from_slice(line) else {
+            return Ok(events);
+        };
+
+        if let Some(message) = data.message {
+            if !message.thinking.is_empty() {
+                self.switch(Mode::Reasoning, &mut events);
+
+                events.push(Event::TextChanged {
+                    delta: message.thinking,
+                    duration: self.mode_started_at.elapsed(),
+                });
+            }
+
+            if !message.content.is_empty() {
+                self.switch(Mode::Messaging, &mut events);
+
+                events.push(Event::TextChanged {
+                    delta: message.content,
+                    duration: self.mode_started_at.elapsed(),
+                });
+            }
+
+            if !message.tool_calls.is_empty() {
+                self.switch(Mode::ToolCalling, &mut events);
+
+                for call in message.tool_calls {
+                    let index = self.calls;
+                    self.calls += 1;
+
+                    events.push(Event::ToolCallAdded {
+                        index,
+                        id: call
+                            .id
+                            .unwrap_or_else(|| tool::Id::new(format!("call_{index}"))),
+                        name: call.function.name,
+                        arguments: call.function.arguments.to_string(),
+                    });
+                }
+            }
+        }
+
+        if let Some(error) = data.error {
+            return Err(Error::CompletionFailed(error));
+        }
+
+        if !data.done {
+            return Ok(events);
+        }
+
+        let prompt_tokens = data.prompt_eval_count.unwrap_or_default();
+        let predicted_tokens = data.eval_count.unwrap_or_default();
+        let prompt_duration = Duration::from_nanos(data.prompt_eval_duration.unwrap_or_default());
+        let predicted_duration = Duration::from_nanos(data.eval_duration.unwrap_or_default());
+
+        let per_second = |tokens: u64, duration: Duration| {
+            if duration.is_zero() {
+                0.0
+            } else {
+                tokens as f64 / duration.as_secs_f64()
+            }
+        };
+
+        events.push(Event::UsageReported {
+            usage: Usage {
+                prompt_tokens,
+                completion_tokens: predicted_tokens,
+                cached_tokens: 0,
+                timings: Some(Timings {
+                    prompt_tokens,
+                    prompt_duration,
+                    prompt_per_second: per_second(prompt_tokens, prompt_duration),
+                    predicted_tokens,
+                    predicted_duration,
+                    predicted_per_second: per_second(predicted_tokens, predicted_duration),
+                }),
+            },
+        });
+
+        let reason = match data.done_reason.as_deref() {
+            Some("stop") | None if self.mode == Some(Mode::ToolCalling) => FinishReason::ToolCalls,
+            Some("stop") | None => FinishReason::Stop,
+            Some("length") => FinishReason::Length,
+            Some(other) => FinishReason::Other(other.to_owned()),
+        };
+
+        events.push(Event::Finished { reason });
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::Error;
+
+use reqwest::StatusCode;
+use tokio::time;
+
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retry {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub statuses: Vec<StatusCode>,
+}
+
+impl Retry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn never() -> Self {
+        Self {
+            max_attempts: Some(1),
+            ..Self::default()
+        }
+    }
+
+    pub fn forever() -> Self {
+        Self {
+            max_attempts: None,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts: Some(max_attempts.max(1)),
+            ..self
+        }
+    }
+
+    pub fn backoff(self, initial: Duration, max: Duration) -> Self {
+        Self {
+            initial_backoff: initial,
+            max_backoff: max,
+            ..self
+        }
+    }
+
+    pub fn multiplier(self, multiplier: f64) -> Self {
+        Self { multiplier, ..self }
+    }
+
+    pub fn jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    pub fn statuses(self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        Self {
+            statuses: statuses.into_iter().collect(),
+            ..self
+        }
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.initial_backoff.as_secs_f64()
+            * self.multiplier.max(1.0).powi(attempt.min(32) as i32);
+
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+
+        let random = std::collections::hash_map::RandomState::new().hash_one(attempt) as f64
+            / u64::MAX as f64;
+
+        let jitter = backoff * self.jitter * (random * 2.0 - 1.0);
+
+        Duration::from_secs_f64((backoff + jitter).max(0.0))
+    }
+
+    pub(crate) fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts
+            .is_none_or(|max_attempts| attempt < max_attempts)
+    }
+
+    pub(crate) fn is_retryable(&self, error: &reqwest::Error) -> bool {
+        if error.is_connect() || error.is_timeout() {
+            return true;
+        }
+
+        error
+            .status()
+            .is_some_and(|status| self.statuses.contains(&status))
+    }
+
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        let mut attempt = 0;
+
+        loop {
+            let Some(request) = request.try_clone() else {
+                return Ok(request.send().await?.error_for_status()?);
+            };
+
+            attempt += 1;
+
+            match request
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                Ok(response) => return Ok(response),
+                Err(error) if self.is_retryable(&error) && self.allows(attempt) => {
+                    time::sleep(self.delay(attempt - 1)).await;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: Some(5),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+            statuses: vec![
+                StatusCode::REQUEST_TIMEOUT,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::Error;
+
 use serde::{Deserialize, Serialize};
 
 pub use skema::Schema;
@@ -15,7 +17,8 @@ pub struct Function {
     pub parameters: Schema,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum Call {
     Function {
         id: Id,
@@ -24,15 +27,33 @@ pub enum Call {
     },
 }
 
-#[derive(Debug, Clone)]
+impl Call {
+    pub fn id(&self) -> &Id {
+        match self {
+            Call::Function { id, .. } => id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Response {
     pub id: Id,
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Id(String);
 
+impl Id {
+    pub(crate) fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
+pub trait Handler {
+    fn call(&mut self, call: &Call) -> impl Future<Output = Result<String, Error>>;
+}
+
 #[cfg(feature = "techne")]
 mod techne {
     use super::*;
@@ -0,0 +1,28 @@
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(data: T) -> Self {
+        Self {
+            version: Self::VERSION,
+            data,
+        }
+    }
+
+    pub fn into_inner(self) -> Result<T, Error> {
+        if self.version != Self::VERSION {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+
+        Ok(self.data)
+    }
+}
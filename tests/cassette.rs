@@ -0,0 +1,48 @@
+use reason::mock::{self, Completion};
+use reason::{Cassette, Error, Message, Reason};
+
+use std::env;
+
+#[tokio::test]
+async fn it_replays_recorded_completions() -> anyhow::Result<()> {
+    let path = env::temp_dir().join(format!("reason-cassette-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let server = mock::Server::start().await?;
+
+    server.respond(
+        Completion::new()
+            .text("Let me check.")
+            .tool_call("call_0", "weather", r#"{"city":"#)
+            .arguments(r#""Paris"}"#)
+            .finish("tool_calls")
+            .usage(12, 8)
+            .fragmented(5),
+    );
+
+    let messages = [
+        Message::system("You are a helpful assistant"),
+        Message::user("What is the weather in Paris?"),
+    ];
+
+    let recorder = Reason::connect(server.url(), "mock")
+        .await?
+        .with_cassette(Cassette::record(&path));
+
+    let recorded = recorder.reply(&messages, &[], &[]).await?;
+
+    let player = Reason::replay("mock", Cassette::replay(&path))?;
+    let replayed = player.reply(&messages, &[], &[]).await?;
+
+    assert_eq!(replayed, recorded);
+    assert_eq!(server.requests().len(), 1);
+
+    let changed = [Message::user("What is the weather in London?")];
+    let missed = player.reply(&changed, &[], &[]).await;
+
+    assert!(matches!(missed, Err(Error::CassetteMissed(_))));
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use reason::mock::{self, Completion};
+use reason::provider::OpenAiCompatible;
+use reason::{ConnectConfig, Error, FinishReason, Message, Options, Output, Reason, Usage, tool};
+
+#[tokio::test]
+async fn it_splits_reasoning_from_fragmented_text() -> anyhow::Result<()> {
+    let server = mock::Server::start().await?;
+    let reason = Reason::connect(server.url(), "mock").await?;
+
+    server.respond(
+        Completion::new()
+            .think("The user greets me")
+            .text("Hello, ")
+            .text("world!")
+            .finish("stop")
+            .usage(3, 4)
+            .fragmented(3),
+    );
+
+    let messages = [Message::user("Hi!")];
+    let reply = reason.reply(&messages, &[], &[]).await?;
+
+    let [Output::Reasoning(reasoning), Output::Message(message)] = &reply.outputs[..] else {
+        panic!("unexpected outputs: {:?}", reply.outputs);
+    };
+
+    assert_eq!(reasoning.text, "The user greets me");
+    assert_eq!(message, "Hello, world!");
+    assert_eq!(reply.finish_reason, Some(FinishReason::Stop));
+    assert_eq!(
+        reply.usage,
+        Some(Usage {
+            prompt_tokens: 3,
+            completion_tokens: 4,
+            cached_tokens: 0,
+            timings: None,
+        })
+    );
+    assert!(!reply.interrupted);
+
+    let requests = server.requests();
+
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["messages"][0]["content"], "Hi!");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_accumulates_interleaved_tool_calls() -> anyhow::Result<()> {
+    let server = mock::Server::start().await?;
+    let reason = Reason::connect(server.url(), "mock").await?;
+
+    server.respond(
+        Completion::new()
+            .tool_call("call_0", "add", r#"{"a":"#)
+            .tool_call("call_1", "negate", "")
+            .arguments_at(0, r#" 1, "b": 2}"#)
+            .arguments_at(1, r#"{"a": 3}"#)
+            .finish("tool_calls")
+            .fragmented(7),
+    );
+
+    let messages = [Message::user("Compute!")];
+    let reply = reason.reply(&messages, &[], &[]).await?;
+
+    let [Output::ToolCalls(calls)] = &reply.outputs[..] else {
+        panic!("unexpected outputs: {:?}", reply.outputs);
+    };
+
+    let calls: Vec<_> = calls
+        .iter()
+        .map(|call| match call {
+            tool::Call::Function {
+                name, arguments, ..
+            } => (name.as_str(), arguments.as_str()),
+        })
+        .collect();
+
+    assert_eq!(
+        calls,
+        [("add", r#"{"a": 1, "b": 2}"#), ("negate", r#"{"a": 3}"#)]
+    );
+    assert_eq!(reply.finish_reason, Some(FinishReason::ToolCalls));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_reports_truncated_replies() -> anyhow::Result<()> {
+    let server = mock::Server::start().await?;
+    let reason = Reason::connect(server.url(), "mock").await?;
+
+    server.respond(
+        Completion::new()
+            .text("Once upon a")
+            .finish("length")
+            .usage(10, 3),
+    );
+
+    let messages = [Message::user("Tell me a story")];
+    let reply = reason.reply(&messages, &[], &[]).await?;
+
+    let [Output::Message(message)] = &reply.outputs[..] else {
+        panic!("unexpected outputs: {:?}", reply.outputs);
+    };
+
+    assert_eq!(message, "Once upon a");
+    assert_eq!(reply.finish_reason, Some(FinishReason::Length));
+    assert_eq!(
+        reply
+            .usage
+            .map(|usage| (usage.prompt_tokens, usage.completion_tokens)),
+        Some((10, 3))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_adapts_requests_to_each_vendor() -> anyhow::Result<()> {
+    let server = mock::Server::start().await?;
+
+    let llama_cpp = Reason::connect(server.url(), "mock").await?;
+    let openai = Reason::connect_with(
+        server.url(),
+        "mock",
+        ConnectConfig::new().provider(OpenAiCompatible::OpenAi),
+    )
+    .await?;
+
+    let messages = [Message::user("Hi!")];
+    let options = Options::new().max_tokens(16).top_k(40);
+
+    for reason in [&llama_cpp, &openai] {
+        server.respond(Completion::new().text("Hello!").finish("stop"));
+
+        let _reply = reason
+            .reply_with(&messages, &[], &[], options.clone())
+            .await?;
+    }
+
+    let requests = server.requests();
+
+    assert_eq!(requests[0]["tools"], serde_json::json!([]));
+    assert_eq!(requests[0]["cache_prompt"], true);
+    assert_eq!(requests[0]["max_tokens"], 16);
+    assert_eq!(requests[0]["top_k"], 40);
+
+    assert!(requests[1].get("tools").is_none());
+    assert!(requests[1].get("cache_prompt").is_none());
+    assert!(requests[1].get("top_k").is_none());
+    assert_eq!(requests[1]["max_completion_tokens"], 16);
+
+    for request in &requests {
+        assert_eq!(request["stream_options"]["include_usage"], true);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn it_fails_on_mid_stream_errors() -> anyhow::Result<()> {
+    let server = mock::Server::start().await?;
+    let reason = Reason::connect(server.url(), "mock").await?;
+
+    server.respond(Completion::new().text("Hel").data(serde_json::json!({
+        "error": {
+            "code": 500,
+            "message": "context shift is disabled",
+            "type": "server_error",
+        },
+    })));
+
+    let messages = [Message::user("Hi!")];
+    let result = reason.reply(&messages, &[], &[]).await;
+
+    assert!(matches!(
+        result,
+        Err(Error::CompletionFailed(message)) if message == "context shift is disabled"
+    ));
+
+    Ok(())
+}
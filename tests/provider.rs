@@ -0,0 +1,380 @@
+use reason::provider::{self, Anthropic, Ollama, OpenAiCompatible, Provider};
+use reason::{Error, FinishReason, Image, Message, Options, Output, Part, Reasoning, Reply, tool};
+
+use serde_json::{Value, json};
+
+use std::time::Duration;
+
+fn decode(provider: &dyn Provider, lines: &[&str]) -> Result<Reply, Error> {
+    let mut decoder = provider.decoder();
+
+    let mut reply = Reply {
+        outputs: Vec::new(),
+        usage: None,
+        finish_reason: None,
+        interrupted: false,
+    };
+
+    for line in lines {
+        for event in decoder.decode(line.as_bytes())? {
+            reply.update(&event);
+        }
+    }
+
+    Ok(reply)
+}
+
+fn body(provider: &dyn Provider, messages: &[Message], options: &Options) -> anyhow::Result<Value> {
+    let request = provider
+        .chat(
+            &reqwest::Client::new(),
+            "http://localhost",
+            provider::Request {
+                model: "model",
+                messages,
+                append: &[],
+                tools: &[],
+                options,
+            },
+        )?
+        .build()?;
+
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default();
+
+    Ok(serde_json::from_slice(body)?)
+}
+
+fn texts(reply: &Reply) -> Vec<&str> {
+    reply.outputs.iter().filter_map(Output::text).collect()
+}
+
+fn calls(reply: &Reply) -> Vec<(&str, &str)> {
+    reply
+        .outputs
+        .iter()
+        .filter_map(|output| match output {
+            Output::ToolCalls(calls) => Some(calls),
+            _ => None,
+        })
+        .flatten()
+        .map(|call| match call {
+            tool::Call::Function {
+                name, arguments, ..
+            } => (name.as_str(), arguments.as_str()),
+        })
+        .collect()
+}
+
+#[test]
+fn it_decodes_anthropic_messages() -> Result<(), Error> {
+    let reply = decode(
+        &Anthropic::new(),
+        &[
+            "event: message_start",
+            r#"data: {"type":"message_start","message":{"usage":{"input_tokens":10,"cache_read_input_tokens":4,"output_tokens":1}}}"#,
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Weather "}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"lookup"}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQBCgIYAhIM"}}"#,
+            r#"data: {"type":"content_block_stop","index":0}"#,
+            r#"data: {"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Checking."}}"#,
+            r#"data: {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_0","name":"weather","input":{}}}"#,
+            r#"data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"city\":"}}"#,
+            r#"data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"\"Paris\"}"}}"#,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":20}}"#,
+            r#"data: {"type":"message_stop"}"#,
+        ],
+    )?;
+
+    assert_eq!(texts(&reply), ["Weather lookup", "Checking."]);
+    assert!(matches!(
+        &reply.outputs[0],
+        Output::Reasoning(Reasoning { signature: Some(signature), .. }) if signature == "EqQBCgIYAhIM"
+    ));
+    assert_eq!(calls(&reply), [("weather", r#"{"city":"Paris"}"#)]);
+    assert_eq!(reply.finish_reason, Some(FinishReason::ToolCalls));
+
+    let usage = reply.usage.expect("usage must be reported");
+
+    assert_eq!(usage.prompt_tokens, 14);
+    assert_eq!(usage.cached_tokens, 4);
+    assert_eq!(usage.completion_tokens, 20);
+
+    Ok(())
+}
+
+#[test]
+fn it_sends_signed_thinking_back_to_anthropic() -> anyhow::Result<()> {
+    let call = tool::Call::Function {
+        id: serde_json::from_value(json!("toolu_0"))?,
+        name: "weather".to_owned(),
+        arguments: r#"{"city":"Paris"}"#.to_owned(),
+    };
+
+    let messages = [
+        Message::user("What is the weather in Paris?"),
+        Message::Assistant(Output::Reasoning(Reasoning {
+            text: "Weather lookup".to_owned(),
+            duration: Duration::from_secs(1),
+            signature: Some("EqQBCgIYAhIM".to_owned()),
+        })),
+        Message::Assistant(Output::Reasoning(Reasoning {
+            text: "Unsigned".to_owned(),
+            duration: Duration::from_secs(1),
+            signature: None,
+        })),
+        Message::Assistant(Output::ToolCalls(vec![call.clone()])),
+        Message::Tool(tool::Response {
+            id: call.id().clone(),
+            content: "Sunny".to_owned(),
+        }),
+    ];
+
+    let body = body(
+        &Anthropic::new().thinking(1024),
+        &messages,
+        &Options::default(),
+    )?;
+
+    assert_eq!(
+        body["messages"][1],
+        json!({
+            "role": "assistant",
+            "content": [
+                {
+                    "type": "thinking",
+                    "thinking": "Weather lookup",
+                    "signature": "EqQBCgIYAhIM",
+                },
+                {
+                    "type": "tool_use",
+                    "id": "toolu_0",
+                    "name": "weather",
+                    "input": { "city": "Paris" },
+                },
+            ],
+        })
+    );
+    assert_eq!(body["messages"][2]["content"][0]["type"], "tool_result");
+
+    Ok(())
+}
+
+#[test]
+fn it_checks_anthropic_health_with_credentials() -> anyhow::Result<()> {
+    let request = Anthropic::new()
+        .api_key("secret")
+        .health(&reqwest::Client::new(), "https://api.anthropic.com")
+        .build()?;
+
+    assert_eq!(
+        request.url().as_str(),
+        "https://api.anthropic.com/v1/models"
+    );
+    assert_eq!(request.headers()["x-api-key"], "secret");
+    assert_eq!(request.headers()["anthropic-version"], Anthropic::VERSION);
+
+    Ok(())
+}
+
+#[test]
+fn it_sends_api_keys_the_way_each_provider_expects() {
+    let (name, value) = Anthropic::new().authorization("secret");
+
+    assert_eq!(name, "x-api-key");
+    assert_eq!(value, "secret");
+
+    let (name, value) = OpenAiCompatible::OpenAi.authorization("secret");
+
+    assert_eq!(name, reqwest::header::AUTHORIZATION);
+    assert_eq!(value, "Bearer secret");
+}
+
+#[test]
+fn it_rejects_options_anthropic_cannot_send() {
+    let messages = [Message::user("Hi!")];
+
+    for (options, unsupported) in [
+        (Options::new().seed(42), "seeds"),
+        (Options::new().grammar("root ::= \"yes\""), "grammars"),
+        (Options::new().repeat_penalty(1.1), "repeat penalties"),
+    ] {
+        let result = Anthropic::new().chat(
+            &reqwest::Client::new(),
+            "http://localhost",
+            provider::Request {
+                model: "model",
+                messages: &messages,
+                append: &[],
+                tools: &[],
+                options: &options,
+            },
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::Unsupported { provider: "Anthropic", feature }) if feature == unsupported
+        ));
+    }
+}
+
+#[test]
+fn it_decodes_ollama_chats() -> Result<(), Error> {
+    let reply = decode(
+        &Ollama::new(),
+        &[
+            r#"{"message":{"role":"assistant","content":"","thinking":"Short answer"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"Hello"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":" there"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"length","prompt_eval_count":8,"prompt_eval_duration":2000000,"eval_count":3,"eval_duration":1000000}"#,
+        ],
+    )?;
+
+    assert_eq!(texts(&reply), ["Short answer", "Hello there"]);
+    assert_eq!(reply.finish_reason, Some(FinishReason::Length));
+
+    let usage = reply.usage.expect("usage must be reported");
+
+    assert_eq!(usage.prompt_tokens, 8);
+    assert_eq!(usage.completion_tokens, 3);
+
+    Ok(())
+}
+
+#[test]
+fn it_names_tool_results_for_ollama() -> anyhow::Result<()> {
+    // Ollama ids are made up per reply, so both turns use call_0
+    let weather = tool::Call::Function {
+        id: serde_json::from_value(json!("call_0"))?,
+        name: "weather".to_owned(),
+        arguments: r#"{"city":"Paris"}"#.to_owned(),
+    };
+
+    let forecast = tool::Call::Function {
+        id: serde_json::from_value(json!("call_0"))?,
+        name: "forecast".to_owned(),
+        arguments: r#"{"city":"Paris","days":3}"#.to_owned(),
+    };
+
+    let messages = [
+        Message::user("What is the weather in Paris?"),
+        Message::Assistant(Output::ToolCalls(vec![weather.clone()])),
+        Message::Tool(tool::Response {
+            id: weather.id().clone(),
+            content: "Sunny".to_owned(),
+        }),
+        Message::Assistant(Output::ToolCalls(vec![forecast.clone()])),
+        Message::Tool(tool::Response {
+            id: forecast.id().clone(),
+            content: "Rain on Tuesday".to_owned(),
+        }),
+    ];
+
+    let body = body(&Ollama::new(), &messages, &Options::default())?;
+
+    assert_eq!(
+        body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+        json!({ "city": "Paris" })
+    );
+    assert_eq!(
+        body["messages"][2],
+        json!({
+            "role": "tool",
+            "content": "Sunny",
+            "tool_name": "weather",
+        })
+    );
+    assert_eq!(
+        body["messages"][4],
+        json!({
+            "role": "tool",
+            "content": "Rain on Tuesday",
+            "tool_name": "forecast",
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_image_urls_for_ollama() -> anyhow::Result<()> {
+    let messages = [Message::Multimodal(vec![
+        Part::Text("What is this?".to_owned()),
+        Part::Image(Image::Url("https://example.com/cat.png".parse()?)),
+    ])];
+
+    let result = Ollama::new().chat(
+        &reqwest::Client::new(),
+        "http://localhost",
+        provider::Request {
+            model: "model",
+            messages: &messages,
+            append: &[],
+            tools: &[],
+            options: &Options::default(),
+        },
+    );
+
+    assert!(matches!(
+        result,
+        Err(Error::Unsupported {
+            provider: "Ollama",
+            feature: "image URLs"
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_on_error_events() {
+    let anthropic = decode(
+        &Anthropic::new(),
+        &[
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        ],
+    );
+
+    let ollama = decode(
+        &Ollama::new(),
+        &[
+            r#"{"message":{"role":"assistant","content":"Hi"},"done":false}"#,
+            r#"{"error":"model runner has unexpectedly stopped"}"#,
+        ],
+    );
+
+    let openai = decode(
+        &OpenAiCompatible::OpenAi,
+        &[r#"data: {"error":{"message":"The server had an error","type":"server_error"}}"#],
+    );
+
+    let llama_cpp = decode(
+        &OpenAiCompatible::LlamaCpp,
+        &[
+            r#"data: {"choices":[{"index":0,"delta":{"content":"Hi"},"finish_reason":null}]}"#,
+            r#"error: {"code":500,"message":"context shift is disabled","type":"server_error"}"#,
+        ],
+    );
+
+    assert!(matches!(
+        anthropic,
+        Err(Error::CompletionFailed(message)) if message == "overloaded_error: Overloaded"
+    ));
+    assert!(matches!(
+        ollama,
+        Err(Error::CompletionFailed(message)) if message == "model runner has unexpectedly stopped"
+    ));
+    assert!(matches!(
+        openai,
+        Err(Error::CompletionFailed(message)) if message == "The server had an error"
+    ));
+    assert!(matches!(
+        llama_cpp,
+        Err(Error::CompletionFailed(message)) if message == "context shift is disabled"
+    ));
+}
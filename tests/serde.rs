@@ -0,0 +1,219 @@
+use reason::{
+    Error, Event, FinishReason, Image, Message, Output, Part, Reasoning, Reply, Timings, Usage,
+    Versioned, tool,
+};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use std::fmt::Debug;
+use std::time::Duration;
+
+fn round_trip<T>(value: &T) -> anyhow::Result<()>
+where
+    T: Debug + PartialEq + Serialize + DeserializeOwned,
+{
+    let json = serde_json::to_string(value)?;
+    let decoded: T = serde_json::from_str(&json)?;
+
+    assert_eq!(&decoded, value, "{json}");
+
+    Ok(())
+}
+
+fn id(id: &str) -> anyhow::Result<tool::Id> {
+    Ok(serde_json::from_value(json!(id))?)
+}
+
+fn usage() -> Usage {
+    Usage {
+        prompt_tokens: 42,
+        completion_tokens: 7,
+        cached_tokens: 30,
+        timings: Some(Timings {
+            prompt_tokens: 12,
+            prompt_duration: Duration::from_millis(120),
+            prompt_per_second: 100.0,
+            predicted_tokens: 7,
+            predicted_duration: Duration::from_millis(350),
+            predicted_per_second: 20.0,
+        }),
+    }
+}
+
+#[test]
+fn it_round_trips_conversations() -> anyhow::Result<()> {
+    let messages = vec![
+        Message::system("You are a helpful assistant"),
+        Message::user("Describe this image"),
+        Message::Multimodal(vec![
+            Part::Text("And this one?".to_owned()),
+            Part::Image(Image::from_bytes(vec![0, 159, 146, 150, 255], "image/png")),
+            Part::Image(Image::Url("https://example.com/cat.png".parse()?)),
+        ]),
+        Message::Assistant(Output::Reasoning(Reasoning {
+            text: "Two images".to_owned(),
+            duration: Duration::from_millis(2750),
+            signature: Some("EqQBCgIYAhIM".to_owned()),
+        })),
+        Message::Assistant(Output::Message("A cat.".to_owned())),
+        Message::Assistant(Output::ToolCalls(vec![tool::Call::Function {
+            id: id("call_0")?,
+            name: "zoom".to_owned(),
+            arguments: r#"{"factor":2}"#.to_owned(),
+        }])),
+        Message::Tool(tool::Response {
+            id: id("call_0")?,
+            content: "Zoomed in".to_owned(),
+        }),
+    ];
+
+    round_trip(&Versioned::new(messages))
+}
+
+#[test]
+fn it_round_trips_replies() -> anyhow::Result<()> {
+    round_trip(&Reply {
+        outputs: vec![
+            Output::Reasoning(Reasoning {
+                text: "Short".to_owned(),
+                duration: Duration::from_secs(1),
+                signature: None,
+            }),
+            Output::Message("Done.".to_owned()),
+        ],
+        usage: Some(usage()),
+        finish_reason: Some(FinishReason::StopSequence("</answer>".to_owned())),
+        interrupted: true,
+    })
+}
+
+#[test]
+fn it_round_trips_events() -> anyhow::Result<()> {
+    let events = [
+        Event::OutputAdded {
+            output: Output::Message(String::new()),
+        },
+        Event::TextChanged {
+            delta: "Hello".to_owned(),
+            duration: Duration::from_micros(1500),
+        },
+        Event::ToolCallAdded {
+            index: 1,
+            id: id("call_1")?,
+            name: "search".to_owned(),
+            arguments: "{".to_owned(),
+        },
+        Event::ArgumentsChanged {
+            index: 1,
+            delta: "}".to_owned(),
+            duration: Duration::from_millis(20),
+        },
+        Event::ReasoningSigned {
+            signature: "EqQBCgIYAhIM".to_owned(),
+        },
+        Event::UsageReported { usage: usage() },
+        Event::Finished {
+            reason: FinishReason::Other("cancelled".to_owned()),
+        },
+    ];
+
+    for event in &events {
+        round_trip(event)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_a_stable_format() -> anyhow::Result<()> {
+    let messages = Versioned::new(vec![
+        Message::Multimodal(vec![
+            Part::Text("What is this?".to_owned()),
+            Part::Image(Image::from_bytes(vec![1, 2, 3], "image/png")),
+        ]),
+        Message::Assistant(Output::Reasoning(Reasoning {
+            text: "A pixel".to_owned(),
+            duration: Duration::from_millis(1500),
+            signature: None,
+        })),
+        Message::Assistant(Output::ToolCalls(vec![tool::Call::Function {
+            id: id("call_0")?,
+            name: "zoom".to_owned(),
+            arguments: "{}".to_owned(),
+        }])),
+        Message::Tool(tool::Response {
+            id: id("call_0")?,
+            content: "ok".to_owned(),
+        }),
+    ]);
+
+    let fixture = json!({
+        "version": 1,
+        "data": [
+            {
+                "role": "multimodal",
+                "content": [
+                    { "type": "text", "content": "What is this?" },
+                    {
+                        "type": "image",
+                        "content": { "type": "data", "mime": "image/png", "data": "AQID" },
+                    },
+                ],
+            },
+            {
+                "role": "assistant",
+                "content": {
+                    "type": "reasoning",
+                    "content": {
+                        "text": "A pixel",
+                        "duration": { "secs": 1, "nanos": 500000000 },
+                        "signature": null,
+                    },
+                },
+            },
+            {
+                "role": "assistant",
+                "content": {
+                    "type": "tool_calls",
+                    "content": [
+                        { "type": "function", "id": "call_0", "name": "zoom", "arguments": "{}" },
+                    ],
+                },
+            },
+            {
+                "role": "tool",
+                "content": { "id": "call_0", "content": "ok" },
+            },
+        ],
+    });
+
+    assert_eq!(serde_json::to_value(&messages)?, fixture);
+    assert_eq!(
+        serde_json::from_value::<Versioned<Vec<Message>>>(fixture)?,
+        messages
+    );
+
+    assert_eq!(
+        serde_json::to_value(FinishReason::StopSequence("END".to_owned()))?,
+        json!({ "type": "stop_sequence", "value": "END" })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unknown_versions() -> anyhow::Result<()> {
+    let versioned: Versioned<Vec<Message>> = serde_json::from_value(json!({
+        "version": 2,
+        "data": [{ "role": "user", "content": "Hi!" }],
+    }))?;
+
+    assert!(matches!(
+        versioned.into_inner(),
+        Err(Error::UnsupportedVersion(2))
+    ));
+
+    Ok(())
+}